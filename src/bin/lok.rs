@@ -0,0 +1,349 @@
+//! Command-line tool for working with LOK puzzles natively, e.g. for batch-verifying a library of puzzles.
+//!
+//! Puzzles and moves are read from files in the same formats that the web app uses: one row of the puzzle per line, and
+//! one move per line as written by `Board::export_moves`.
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use lok_wasm::generator::{self, GeneratorOptions};
+use lok_wasm::logging::{self, LogLevel, StderrSink};
+use lok_wasm::pack::PuzzlePack;
+use lok_wasm::replay::FrameFormat;
+use lok_wasm::{Board, SolutionResult};
+
+const USAGE: &str = "\
+Usage: lok [-v | -vv] <command> <args>
+
+Options:
+  -v   Logs each move to stderr.
+  -vv  Logs every step of checking the rules to stderr.
+
+Commands:
+  check <puzzle> <moves>   Checks whether the moves solve the puzzle.
+  solve <puzzle>           Prints a solution to the puzzle, one move per line.
+  lint <puzzle>...         Checks that each puzzle has exactly one solution.
+  render <puzzle> [moves]  Prints the puzzle, with [L] for blackened cells and (L)
+                           for cells marked for a path.
+  svg <puzzle> [moves [step]]
+                           Prints the puzzle as an SVG image, after the given
+                           number of moves or all of them.
+  replay <puzzle> <moves>  Prints the board after each move, with the keyword
+                           being gathered or executed.
+  rate <puzzle>...         Prints how hard each puzzle is, and why.
+  pack <pack>              Checks each reference solution in a puzzle pack, or
+                           that the puzzle is solvable if it has none, and
+                           that no puzzle is a rotation or reflection of another.
+  generate <width> <height> [seed]
+                           Prints a random puzzle, a blank line, and its solution.
+
+Exit codes:
+  0   the solution is correct, or the command succeeded
+  1   the solution is incomplete, a puzzle has no solution, lint found problems,
+      or no puzzle could be generated
+  2   a keyword was left partially executed
+  3   a keyword was left partially gathered
+  4   a move was illegal
+  64  the command line was wrong, e.g. a size, seed, or step isn't a number, or
+      the step is past the last move
+  65  a puzzle or moves file couldn't be read or parsed";
+
+const EXIT_FAILURE: u8 = 1;
+const EXIT_USAGE: u8 = 64;
+const EXIT_BAD_INPUT: u8 = 65;
+
+/// Gets the exit code that reports a solution result to scripts.
+fn result_exit_code(result: &SolutionResult) -> u8 {
+    match result {
+        SolutionResult::Correct => 0,
+        SolutionResult::Incomplete => EXIT_FAILURE,
+        SolutionResult::NotIdle => 2,
+        SolutionResult::PartialKeyword => 3,
+        SolutionResult::ErrorOnMove(_, _) => 4,
+    }
+}
+
+/// Loads a puzzle from a file and applies the moves from another file, if given.
+fn load_board(puzzle_path: &str, moves_path: Option<&str>) -> Result<Board, String> {
+    let puzzle = fs::read_to_string(puzzle_path)
+        .map_err(|err| format!("Couldn't read puzzle {}: {}", puzzle_path, err))?;
+    let mut board = Board::new(&puzzle).map_err(|err| format!("{}: {}", puzzle_path, err))?;
+
+    if let Some(moves_path) = moves_path {
+        let moves = fs::read_to_string(moves_path)
+            .map_err(|err| format!("Couldn't read moves {}: {}", moves_path, err))?;
+        board
+            .import_moves(&moves)
+            .map_err(|err| format!("{}: {}", moves_path, err))?;
+    }
+
+    Ok(board)
+}
+
+/// Runs a command on each of several puzzles, reporting on all of them. A puzzle that can't be loaded is reported and
+/// skipped. Returns the worst exit code of any of them.
+fn for_each_puzzle(puzzle_paths: &[&str], command: impl Fn(&str, &Board) -> u8) -> u8 {
+    let mut code = 0;
+    for puzzle_path in puzzle_paths.iter() {
+        match load_board(puzzle_path, None) {
+            Ok(board) => code = code.max(command(puzzle_path, &board)),
+            Err(err) => {
+                eprintln!("{}", err);
+                code = code.max(EXIT_BAD_INPUT);
+            }
+        }
+    }
+
+    code
+}
+
+fn check(board: &Board) -> u8 {
+    let result = board.check_solution();
+    match &result {
+        SolutionResult::ErrorOnMove(mv_num, err) => {
+            let mv = board.moves().nth(*mv_num).unwrap();
+            println!("ErrorOnMove: move {} ({}): {:?}", mv_num + 1, mv, err);
+        }
+        _ => println!("{}", result.name()),
+    }
+
+    result_exit_code(&result)
+}
+
+fn solve(board: &Board) -> u8 {
+    match board.solve() {
+        Some(solution) => {
+            for mv in solution.iter() {
+                println!("{}", mv);
+            }
+            0
+        }
+        None => {
+            println!("No solution");
+            EXIT_FAILURE
+        }
+    }
+}
+
+fn lint(puzzle_path: &str, board: &Board) -> u8 {
+    let solutions = board.enumerate_solutions(2);
+    match solutions.count() {
+        0 => {
+            println!("{}: no solution", puzzle_path);
+            EXIT_FAILURE
+        }
+        1 => {
+            println!("{}: OK", puzzle_path);
+            0
+        }
+        _ => {
+            println!("{}: more than one solution, for example:", puzzle_path);
+            for solution in solutions.representatives.iter() {
+                let moves: Vec<String> = solution.iter().map(|mv| mv.to_string()).collect();
+                println!("  {}", moves.join(", "));
+            }
+            EXIT_FAILURE
+        }
+    }
+}
+
+fn rate(puzzle_path: &str, board: &Board) -> u8 {
+    match board.rate_difficulty() {
+        Ok(difficulty) => {
+            println!("{}: {:.1}", puzzle_path, difficulty.score);
+            for factor in difficulty.factors.iter() {
+                println!("  {:<20} {:>5.1}", factor.name, factor.points);
+            }
+            0
+        }
+        Err(_) => {
+            println!("{}: no solution", puzzle_path);
+            EXIT_FAILURE
+        }
+    }
+}
+
+fn check_pack(pack_path: &str) -> Result<u8, String> {
+    let text = fs::read_to_string(pack_path)
+        .map_err(|err| format!("Couldn't read pack {}: {}", pack_path, err))?;
+    let pack: PuzzlePack = text
+        .parse()
+        .map_err(|err| format!("{}: {}", pack_path, err))?;
+
+    let mut code = 0;
+    let mut seen = HashMap::new();
+    for (i, puzzle) in pack.iter().enumerate() {
+        let name = match puzzle.title() {
+            Some(title) => format!("{} (\"{}\")", i + 1, title),
+            None => (i + 1).to_string(),
+        };
+
+        // Puzzles that are rotations or reflections of each other play the same way, so they count as duplicates.
+        let hash = puzzle.board().canonical_hash();
+        if let Some(original) = seen.get(&hash) {
+            println!("{}: duplicate of {}", name, original);
+            code = code.max(EXIT_FAILURE);
+            continue;
+        }
+        seen.insert(hash, name.clone());
+
+        match puzzle.board_with_solution() {
+            Some(board) => {
+                let result = board.check_solution();
+                println!("{}: {}", name, result.name());
+                code = code.max(result_exit_code(&result));
+            }
+            None if puzzle.board().is_solvable() => println!("{}: solvable", name),
+            None => {
+                println!("{}: no solution", name);
+                code = code.max(EXIT_FAILURE);
+            }
+        }
+    }
+
+    Ok(code)
+}
+
+fn render(board: &Board) -> u8 {
+    println!("{}", board);
+    0
+}
+
+fn replay(board: &Board) -> u8 {
+    for frame in board.replay(FrameFormat::Text) {
+        match frame.mv {
+            Some(mv) => println!("{}. {}: {}", frame.step, mv, frame.caption),
+            None => println!("{}", frame.caption),
+        }
+        println!("{}", frame.board);
+        println!();
+    }
+
+    0
+}
+
+fn svg(board: &Board, step: Option<&str>) -> u8 {
+    let step = match step {
+        Some(step) => match step.parse::<usize>() {
+            Ok(step) => step,
+            Err(_) => {
+                eprintln!("The step \"{}\" isn't a number", step);
+                return EXIT_USAGE;
+            }
+        },
+        None => board.move_count(),
+    };
+
+    match board.render_svg(step) {
+        Ok(svg) => {
+            print!("{}", svg);
+            0
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            EXIT_USAGE
+        }
+    }
+}
+
+/// Parses the size and seed for `generate`. With no seed, a different puzzle is generated every second.
+fn generator_options(
+    width: &str,
+    height: &str,
+    seed: Option<&str>,
+) -> Result<GeneratorOptions, String> {
+    fn parse<T: FromStr>(name: &str, value: &str) -> Result<T, String> {
+        value
+            .parse()
+            .map_err(|_| format!("The {} \"{}\" isn't a number", name, value))
+    }
+
+    let seed = match seed {
+        Some(seed) => parse("seed", seed)?,
+        None => SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs()),
+    };
+
+    Ok(GeneratorOptions::new(
+        parse("width", width)?,
+        parse("height", height)?,
+        seed,
+    ))
+}
+
+fn generate(width: &str, height: &str, seed: Option<&str>) -> u8 {
+    let options = match generator_options(width, height, seed) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}", err);
+            return EXIT_USAGE;
+        }
+    };
+
+    match generator::generate(&options) {
+        Ok(generated) => {
+            println!("{}", generated.get_puzzle());
+            println!();
+            println!("{}", generated.get_solution());
+            0
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            EXIT_FAILURE
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<u8, String> {
+    let mut args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    let log_level = match args.first() {
+        Some(&"-v") => LogLevel::Info,
+        Some(&"-vv") => LogLevel::Trace,
+        _ => LogLevel::Off,
+    };
+
+    if log_level != LogLevel::Off {
+        args.remove(0);
+        logging::set_log_sink(Some(Box::new(StderrSink)));
+        logging::set_log_level(log_level);
+    }
+
+    let code = match args.as_slice() {
+        ["check", puzzle, moves] => check(&load_board(puzzle, Some(moves))?),
+        ["solve", puzzle] => solve(&load_board(puzzle, None)?),
+        ["lint", puzzles @ ..] if !puzzles.is_empty() => for_each_puzzle(puzzles, lint),
+        ["rate", puzzles @ ..] if !puzzles.is_empty() => for_each_puzzle(puzzles, rate),
+        ["pack", pack] => check_pack(pack)?,
+        ["render", puzzle] => render(&load_board(puzzle, None)?),
+        ["render", puzzle, moves] => render(&load_board(puzzle, Some(moves))?),
+        ["replay", puzzle, moves] => replay(&load_board(puzzle, Some(moves))?),
+        ["svg", puzzle] => svg(&load_board(puzzle, None)?, None),
+        ["svg", puzzle, moves] => svg(&load_board(puzzle, Some(moves))?, None),
+        ["svg", puzzle, moves, step] => svg(&load_board(puzzle, Some(moves))?, Some(step)),
+        ["generate", width, height] => generate(width, height, None),
+        ["generate", width, height, seed] => generate(width, height, Some(seed)),
+        _ => {
+            eprintln!("{}", USAGE);
+            EXIT_USAGE
+        }
+    };
+
+    Ok(code)
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match run(&args) {
+        Ok(code) => ExitCode::from(code),
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::from(EXIT_BAD_INPUT)
+        }
+    }
+}
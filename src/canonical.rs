@@ -0,0 +1,183 @@
+//! Finding puzzles that are the same as each other up to rotation or reflection.
+//!
+//! Most of the rules don't care which way the board is turned: keywords are gathered in a straight line in any of the
+//! four directions, and adjacency works the same way in all of them. The letters of a keyword still have to be gathered
+//! in order, but that order is along the path, not across the board. The one exception is LOLO, whose diagonal always
+//! runs from the lower-left to the upper-right. Turning the board a quarter turn or mirroring it makes that the other
+//! diagonal, so those transforms only preserve the rules for puzzles where LOLO can never come up.
+
+use wasm_bindgen::prelude::*;
+
+use crate::grid::Transform;
+use crate::keyword::KeywordRegistry;
+use crate::utils::fnv1a;
+use crate::{Board, BoardGrid, Move, BLANK_LETTER, WILDCARD_LETTER};
+
+/// The transforms that keep LOLO's diagonal running from the lower-left to the upper-right.
+const LOLO_SYMMETRIES: [Transform; 4] = [
+    Transform::Identity,
+    Transform::Rotate180,
+    Transform::Transpose,
+    Transform::AntiTranspose,
+];
+
+/// Tells whether LOLO could ever be gathered on the grid. Blanks can be filled in by BE and wildcards can be changed to
+/// any letter, so they count as either letter.
+fn could_gather_lolo(grid: &BoardGrid) -> bool {
+    let count = |wanted: &[char]| {
+        grid.iter()
+            .filter(|cell| {
+                cell.get_letter_or_blank()
+                    .is_some_and(|letter| wanted.contains(&letter))
+            })
+            .count()
+    };
+
+    let flexible = count(&[BLANK_LETTER, WILDCARD_LETTER]);
+    let l = count(&['L']);
+    let o = count(&['O']);
+    l + flexible >= 2 && o + flexible >= 2 && l + o + flexible >= 4
+}
+
+impl Move {
+    /// Gets the same move on a board that was transformed.
+    fn transform(&self, transform: Transform, width: usize, height: usize) -> Move {
+        let rc = transform.apply(self.get_rc(), width, height);
+        match self {
+            Move::Blacken(_) => Move::Blacken(rc),
+            Move::MarkPath(_) => Move::MarkPath(rc),
+            Move::ChangeLetter(_, letter) => Move::ChangeLetter(rc, *letter),
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Board {
+    /// Gets the puzzle in canonical form: of all the rotations and reflections of the puzzle that play the same way,
+    /// the one whose text comes first. Two puzzles that are the same up to those transforms have the same canonical
+    /// form.
+    pub fn canonical_puzzle(&self) -> String {
+        Board::puzzle_text(&self.grid.transform(self.canonical_transform()))
+    }
+
+    /// Gets a hash of the canonical form of the puzzle, for finding duplicates in a library of puzzles. It stays the
+    /// same between builds, so it can be saved.
+    pub fn canonical_hash(&self) -> u64 {
+        fnv1a(&self.canonical_puzzle())
+    }
+}
+
+impl Board {
+    /// Gets the transforms that turn this puzzle into one that plays exactly the same way, with every solution turned
+    /// the same way. That's all of them, unless LOLO could come up. Always starts with `Identity`.
+    pub fn symmetries(&self) -> Vec<Transform> {
+        if could_gather_lolo(&self.grid) {
+            LOLO_SYMMETRIES.to_vec()
+        } else {
+            Transform::ALL.to_vec()
+        }
+    }
+
+    /// Gets the transform that turns this puzzle into its canonical form. See `canonical_puzzle`.
+    pub fn canonical_transform(&self) -> Transform {
+        self.symmetries()
+            .into_iter()
+            .min_by_key(|transform| Board::puzzle_text(&self.grid.transform(*transform)))
+            .unwrap()
+    }
+
+    /// Creates a copy of the board with the puzzle and the moves that are currently applied rotated or reflected. The
+    /// copy recognizes the standard keywords and has no other history.
+    pub fn transformed(&self, transform: Transform) -> Board {
+        let mut board =
+            Board::from_grid(self.grid.transform(transform), KeywordRegistry::standard());
+        board.is_strict = self.is_strict;
+
+        for mv in self.moves() {
+            board.make_move(&mv.transform(transform, self.grid.width(), self.grid.height()));
+        }
+
+        board
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SR;
+
+    #[test]
+    fn mirror_images_without_lolo() {
+        let puzzle = Board::new("LOK_\n-TA-").unwrap();
+        let mirrored = Board::new("_KOL\n-AT-").unwrap();
+        let rotated = Board::new("-L\nTO\nAK\n-_").unwrap();
+
+        assert_eq!(puzzle.symmetries().len(), 8);
+        assert_eq!(puzzle.canonical_puzzle(), mirrored.canonical_puzzle());
+        assert_eq!(puzzle.canonical_hash(), mirrored.canonical_hash());
+        assert_eq!(puzzle.canonical_hash(), rotated.canonical_hash());
+        assert_ne!(
+            puzzle.canonical_hash(),
+            Board::new("LOK_\nT-A-").unwrap().canonical_hash()
+        );
+    }
+
+    #[test]
+    fn lolo_diagonal_is_kept() {
+        let puzzle = Board::new("LOLO\n---L\n--O-\n-L--").unwrap();
+        assert_eq!(
+            puzzle.symmetries(),
+            vec![
+                Transform::Identity,
+                Transform::Rotate180,
+                Transform::Transpose,
+                Transform::AntiTranspose,
+            ]
+        );
+
+        // Mirroring left to right turns the diagonal the wrong way.
+        let mirrored = Board::new("OLOL\nL---\n-O--\n--L-").unwrap();
+        assert_ne!(puzzle.canonical_hash(), mirrored.canonical_hash());
+
+        let transposed = Board::new("L---\nO--L\nL-O-\nOL--").unwrap();
+        assert_eq!(puzzle.canonical_hash(), transposed.canonical_hash());
+    }
+
+    #[test]
+    fn blanks_could_become_lolo() {
+        assert_eq!(Board::new("LOK_").unwrap().symmetries().len(), 8);
+        assert_eq!(Board::new("LOK_\nL__-").unwrap().symmetries().len(), 4);
+        assert_eq!(Board::new("LOK?\nL?--").unwrap().symmetries().len(), 4);
+    }
+
+    #[test]
+    fn transformed_solutions_still_solve() {
+        let mut board = Board::new("TLAK\n-BE_\nLOK-").unwrap();
+        for mv in board.solve().unwrap() {
+            assert!(board.make_move(&mv));
+        }
+        assert_eq!(board.check_solution(), SR::Correct);
+
+        for transform in board.symmetries() {
+            let transformed = board.transformed(transform);
+            assert_eq!(transformed.check_solution(), SR::Correct, "{:?}", transform);
+            assert_eq!(transformed.canonical_puzzle(), board.canonical_puzzle());
+        }
+    }
+
+    #[test]
+    fn canonical_form_is_a_transform() {
+        let board = Board::new("K-\nO_\nL-").unwrap();
+        let transform = board.canonical_transform();
+        assert_eq!(
+            board.canonical_puzzle(),
+            Board::puzzle_text(&board.grid.transform(transform))
+        );
+        assert_eq!(
+            Board::new(&board.canonical_puzzle())
+                .unwrap()
+                .canonical_puzzle(),
+            board.canonical_puzzle()
+        );
+    }
+}
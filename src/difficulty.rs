@@ -0,0 +1,403 @@
+use wasm_bindgen::prelude::*;
+
+use crate::grid::Direction;
+use crate::solver::{alphabet, candidate_moves, without_needless_changes, Solver};
+use crate::utils::Json;
+use crate::{Board, BoardState, Move, Simulation};
+
+// How many points each part of a puzzle adds to its difficulty score. Dead ends are scored on a log scale, since a search
+// can easily hit thousands of them in a large puzzle.
+const POINTS_PER_MOVE: f64 = 0.5;
+const POINTS_PER_BRANCH: f64 = 0.5;
+const POINTS_PER_DOUBLING_OF_DEAD_ENDS: f64 = 3.0;
+const CONDUCTOR_POINTS: f64 = 5.0;
+const WILDCARD_POINTS: f64 = 5.0;
+
+/// Gets how many points executing a keyword adds to a puzzle's difficulty. The basic keywords add nothing, and keywords
+/// with more unusual effects add more. Keywords that aren't part of the standard rules get a middling value.
+fn keyword_points(keyword: &str) -> f64 {
+    match keyword {
+        "LOK" => 0.0,
+        "TLAK" => 2.0,
+        "TA" | "BE" => 5.0,
+        "LOLO" => 8.0,
+        _ => 5.0,
+    }
+}
+
+/// One part of a puzzle's difficulty score.
+#[derive(Clone, PartialEq, Debug)]
+pub struct DifficultyFactor {
+    /// What this part of the score is for, e.g. "solution length" or "keyword LOLO".
+    pub name: String,
+
+    /// How many points this adds to the score.
+    pub points: f64,
+}
+
+/// An estimate of how hard a puzzle is, along with the measurements it's based on.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Difficulty {
+    /// The overall score. Higher is harder. This is always the sum of the points in `factors`.
+    pub score: f64,
+
+    /// The number of moves in the solution that was found.
+    pub solution_length: usize,
+
+    /// The average number of legal moves worth considering at each step along the solution.
+    pub branching_factor: f64,
+
+    /// How many positions a search for the solution found to have no way forward before finding the solution.
+    pub dead_ends: usize,
+
+    /// Whether the puzzle needs a keyword gathered along a path through a conductor, whether the path turns there or
+    /// goes straight through.
+    pub uses_conductors: bool,
+
+    /// Whether the puzzle needs a wildcard changed to another letter.
+    pub uses_wildcards: bool,
+
+    /// Every keyword that the puzzle needs executed, in the order each was first executed in the solution.
+    pub keywords: Vec<String>,
+
+    /// The breakdown of the score.
+    pub factors: Vec<DifficultyFactor>,
+}
+
+impl Difficulty {
+    fn add_factor(&mut self, name: &str, points: f64) {
+        if points > 0.0 {
+            self.score += points;
+            self.factors.push(DifficultyFactor {
+                name: name.to_string(),
+                points,
+            });
+        }
+    }
+}
+
+impl From<&DifficultyFactor> for Json {
+    fn from(factor: &DifficultyFactor) -> Json {
+        Json::Object(vec![
+            ("name", factor.name.as_str().into()),
+            ("points", factor.points.into()),
+        ])
+    }
+}
+
+impl From<&Difficulty> for Json {
+    fn from(difficulty: &Difficulty) -> Json {
+        Json::Object(vec![
+            ("score", difficulty.score.into()),
+            ("solution_length", difficulty.solution_length.into()),
+            ("branching_factor", difficulty.branching_factor.into()),
+            ("dead_ends", difficulty.dead_ends.into()),
+            ("uses_conductors", difficulty.uses_conductors.into()),
+            ("uses_wildcards", difficulty.uses_wildcards.into()),
+            ("keywords", difficulty.keywords.clone().into()),
+            (
+                "factors",
+                Json::Array(difficulty.factors.iter().map(Into::into).collect()),
+            ),
+        ])
+    }
+}
+
+#[wasm_bindgen]
+impl Board {
+    /// Rates how hard the puzzle is, like `rate_difficulty`, and returns the rating as JSON.
+    /// The measurements are fields named the same as in `Difficulty`, and the breakdown is a list of
+    /// `{"name", "points"}` objects.
+    pub fn rate_difficulty_json(&self) -> Result<String, String> {
+        Ok(Json::from(&self.rate_difficulty()?).to_string())
+    }
+}
+
+/// The mechanics that a solution uses.
+struct Mechanics {
+    uses_conductors: bool,
+    uses_wildcards: bool,
+    keywords: Vec<String>,
+}
+
+impl Mechanics {
+    /// Finds the mechanics used by a solution that starts from the given simulation state.
+    fn of_solution(start: &Simulation, solution: &[Move]) -> Mechanics {
+        let mut mechanics = Mechanics {
+            uses_conductors: false,
+            uses_wildcards: false,
+            keywords: vec![],
+        };
+
+        let mut sim = start.clone();
+        for mv in solution.iter() {
+            if let Move::ChangeLetter(rc, _) = mv {
+                mechanics.uses_wildcards |= sim.grid[rc].was_ever_wildcard();
+            }
+
+            mechanics.uses_conductors |= uses_conductor(&sim, mv);
+
+            let was_gathering = matches!(sim.state, BoardState::GatheringKeyword(_, _));
+
+            sim.apply(mv)
+                .expect("solutions should only contain legal moves");
+
+            // The last letter of a keyword was just gathered.
+            if let (true, BoardState::ExecutingKeyword(id, _)) = (was_gathering, &sim.state) {
+                let keyword = sim.keywords.get(*id).letters();
+                if !mechanics.keywords.iter().any(|k| k == keyword) {
+                    mechanics.keywords.push(keyword.to_string());
+                }
+            }
+        }
+
+        mechanics
+    }
+
+    /// Drops the mechanics that the puzzle can be solved without, starting from the given simulation state. The search
+    /// may well have used them anyway, but they don't make the puzzle any harder.
+    fn keep_required(&mut self, start: &Simulation) {
+        if self.uses_conductors {
+            self.uses_conductors =
+                !is_solvable_without(start, |before, mv, _| uses_conductor(before, mv));
+        }
+
+        if self.uses_wildcards {
+            self.uses_wildcards = !is_solvable_without(
+                start,
+                |before, mv, _| matches!(mv, Move::ChangeLetter(rc, _) if before.grid[rc].was_ever_wildcard()),
+            );
+        }
+
+        self.keywords.retain(|keyword| {
+            let id = start.keywords.find(keyword).unwrap();
+            !is_solvable_without(start, move |before, _, after| {
+                matches!(before.state, BoardState::GatheringKeyword(_, _))
+                    && matches!(after.state, BoardState::ExecutingKeyword(executing, _) if executing == id)
+            })
+        });
+    }
+}
+
+/// Returns whether a move uses a conductor: either marking a path on one, or gathering the next letter of a keyword
+/// along a path that passes straight through one.
+fn uses_conductor(before: &Simulation, mv: &Move) -> bool {
+    let last_rc = match (&before.state, mv) {
+        (_, Move::MarkPath(_)) => return true,
+        (BoardState::GatheringKeyword(_, keyword_moves), Move::Blacken(_)) => {
+            match keyword_moves.last() {
+                Some(last_move) => last_move.get_rc(),
+                None => return false,
+            }
+        }
+        _ => return false,
+    };
+
+    let rc = mv.get_rc();
+    match Direction::between(last_rc, rc) {
+        Some(direction) if !direction.is_diagonal() => before
+            .grid
+            .walk(last_rc, direction)
+            .take_while(|(walk_rc, _)| walk_rc != rc)
+            .any(|(_, cell)| cell.is_interactive() && cell.is_conductor()),
+        _ => false,
+    }
+}
+
+/// Returns whether the puzzle can be solved from the given simulation state without ever making a move that `excluded`
+/// returns true for. See `Solver::excluding`.
+fn is_solvable_without(
+    start: &Simulation,
+    excluded: impl Fn(&Simulation, &Move, &Simulation) -> bool + 'static,
+) -> bool {
+    Solver::excluding(&start.keywords, excluded).search(start)
+}
+
+impl Board {
+    /// Estimates how hard the puzzle is to solve from its initial state, ignoring any moves the player has made so far.
+    /// Fails if the puzzle has no solution.
+    ///
+    /// The estimate is based on a solution found by `solve`: how long it is, how many legal moves there are to choose
+    /// from at each step along the way, and how many dead ends the search ran into before finding it. Mechanics that the
+    /// solution uses only count if the puzzle can't be solved without them.
+    ///
+    /// Finding that out takes another full search for each mechanic and each keyword that the solution uses, so this
+    /// can take many times as long as `solve`. The searches for mechanics that the puzzle does need are the slowest,
+    /// since they have to rule out every other way of solving it.
+    pub fn rate_difficulty(&self) -> Result<Difficulty, String> {
+        let start = self.new_simulation();
+        let mut solver = Solver::new(&self.keywords);
+        if !solver.search(&start) {
+            return Err("The puzzle has no solution!".to_string());
+        }
+
+        let solution = without_needless_changes(&start, solver.moves);
+        let alphabet = alphabet(&self.keywords);
+        let mut sim = start.clone();
+        let mut total_branches = 0;
+        let mut last_move: Option<&Move> = None;
+
+        for mv in solution.iter() {
+            total_branches += candidate_moves(&sim, &alphabet, last_move)
+                .iter()
+                .filter(|candidate| sim.clone().apply(candidate).is_ok())
+                .count();
+
+            sim.apply(mv)
+                .expect("solutions should only contain legal moves");
+            last_move = Some(mv);
+        }
+
+        let mut mechanics = Mechanics::of_solution(&start, &solution);
+        mechanics.keep_required(&start);
+
+        let solution_length = solution.len();
+        let mut difficulty = Difficulty {
+            score: 0.0,
+            solution_length,
+            branching_factor: total_branches as f64 / solution_length.max(1) as f64,
+            dead_ends: solver.dead_ends,
+            uses_conductors: mechanics.uses_conductors,
+            uses_wildcards: mechanics.uses_wildcards,
+            keywords: mechanics.keywords,
+            factors: vec![],
+        };
+
+        difficulty.add_factor("solution length", POINTS_PER_MOVE * solution_length as f64);
+        difficulty.add_factor(
+            "branching factor",
+            POINTS_PER_BRANCH * difficulty.branching_factor,
+        );
+        difficulty.add_factor(
+            "dead ends",
+            POINTS_PER_DOUBLING_OF_DEAD_ENDS * (difficulty.dead_ends as f64).ln_1p() / 2f64.ln(),
+        );
+
+        if difficulty.uses_conductors {
+            difficulty.add_factor("conductors", CONDUCTOR_POINTS);
+        }
+
+        if difficulty.uses_wildcards {
+            difficulty.add_factor("wildcards", WILDCARD_POINTS);
+        }
+
+        for keyword in difficulty.keywords.clone().iter() {
+            difficulty.add_factor(&format!("keyword {}", keyword), keyword_points(keyword));
+        }
+
+        Ok(difficulty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rate(puzzle: &str) -> Difficulty {
+        Board::new(puzzle).unwrap().rate_difficulty().unwrap()
+    }
+
+    #[test]
+    fn rate_lok() {
+        let difficulty = rate("LOK_");
+        assert_eq!(difficulty.solution_length, 4);
+        assert_eq!(difficulty.keywords, vec!["LOK".to_string()]);
+        assert!(!difficulty.uses_conductors);
+        assert!(!difficulty.uses_wildcards);
+        assert!(difficulty.score > 0.0 && difficulty.score < 10.0);
+    }
+
+    #[test]
+    fn score_is_sum_of_factors() {
+        let difficulty = rate("LO?_");
+        let sum: f64 = difficulty.factors.iter().map(|factor| factor.points).sum();
+        assert!((difficulty.score - sum).abs() < 1e-9);
+        assert!(difficulty
+            .factors
+            .iter()
+            .any(|factor| factor.name == "solution length"));
+    }
+
+    #[test]
+    fn rate_mechanics() {
+        assert!(rate("LO?_").uses_wildcards);
+        assert!(rate("TXA").uses_conductors);
+        assert!(
+            rate(
+                "TXLX\n\
+             -K--\n\
+             -XAX\n\
+             ----\n\
+             TAX_",
+            )
+            .uses_conductors
+        );
+
+        assert_eq!(
+            rate("BELO__").keywords,
+            vec!["BE".to_string(), "LOK".to_string()]
+        );
+
+        let difficulty = rate(
+            "LOLO\n\
+             --_-\n\
+             -_--",
+        );
+        assert_eq!(difficulty.keywords, vec!["LOLO".to_string()]);
+        assert!(difficulty
+            .factors
+            .iter()
+            .any(|factor| factor.name == "keyword LOLO"));
+    }
+
+    #[test]
+    fn rate_only_required_mechanics() {
+        // The search happens to execute TA here, but BE alone is enough.
+        let difficulty = rate(
+            "?A?_\n\
+             YWEB",
+        );
+        assert!(difficulty.uses_wildcards);
+        assert_eq!(difficulty.keywords, vec!["BE".to_string()]);
+        assert!(!difficulty
+            .factors
+            .iter()
+            .any(|factor| factor.name == "keyword TA"));
+
+        // The search happens to change a wildcard here, but the puzzle doesn't need it.
+        let difficulty = rate(
+            "P-EB\n\
+             -P_A",
+        );
+        assert!(!difficulty.uses_wildcards);
+        assert_eq!(
+            difficulty.keywords,
+            vec!["BE".to_string(), "TA".to_string()]
+        );
+    }
+
+    #[test]
+    fn harder_puzzles_score_higher() {
+        let easy = rate("LOK_");
+        let hard = rate(
+            "LO-_K-\n\
+             L_O_K_\n\
+             TLAK--",
+        );
+        assert!(hard.score > easy.score);
+        assert!(rate("BELO__").score > easy.score);
+    }
+
+    #[test]
+    fn rate_unsolvable() {
+        assert!(Board::new("LOK").unwrap().rate_difficulty().is_err());
+    }
+
+    #[test]
+    fn rate_json() {
+        let json = Board::new("LOK_").unwrap().rate_difficulty_json().unwrap();
+        assert!(json.starts_with(r#"{"score":"#));
+        assert!(json.contains(r#""keywords":["LOK"]"#));
+        assert!(json.contains(r#"{"name":"solution length","points":2}"#));
+    }
+}
@@ -0,0 +1,777 @@
+//! Generating random puzzles that are guaranteed to be solvable.
+//!
+//! A puzzle is generated by making up a valid sequence of moves and working out which letters the board needs for it.
+//! The moves are made up in the order they'll be played, rather than by working backwards from a finished board, but
+//! the result is the same: each cell's letter is decided the first time a move needs that cell, and nothing later ever
+//! changes it, so the finished board is exactly the set of letters the whole sequence needs, whichever end it was built
+//! from. Building forward is simpler because each move can look at what the earlier moves already decided, e.g. which
+//! letters TA has used up. Cells that no move needs become gaps, and the sequence is checked with the real rules
+//! before it's returned.
+
+use wasm_bindgen::prelude::*;
+
+use crate::grid::{Direction, Grid, RC};
+use crate::keyword::KeywordRegistry;
+use crate::{
+    Board, Move, SolutionResult, BLANK_LETTER, CONDUCTOR_LETTER, GAP_LETTER, WILDCARD_LETTER,
+};
+
+// Letters that aren't in any keyword or special, used for cells that only need to be blackened.
+const FILLER_LETTERS: &str = "CDFGHIJMNPQRSUVWYZ";
+
+// How many times to start over from an empty board before giving up.
+const MAX_ATTEMPTS: usize = 100;
+
+// The most cells a generated puzzle can have. Generating fills in every cell many times over, so much bigger boards
+// would take a very long time, if they fit in memory at all.
+const MAX_CELLS: usize = 64 * 64;
+
+// How many times in a row adding a keyword can fail before the board is considered full.
+const MAX_FAILURES: usize = 50;
+
+/// A small, seedable pseudo-random number generator (SplitMix64), so that the same seed always generates the same
+/// puzzle on every platform.
+struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `0..n`.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    /// Returns true with the given percent chance.
+    fn chance(&mut self, percent: u64) -> bool {
+        self.next() % 100 < percent
+    }
+
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            None
+        } else {
+            Some(&items[self.below(items.len())])
+        }
+    }
+}
+
+/// What the generator has decided about a cell so far.
+#[derive(Clone, PartialEq)]
+enum Slot {
+    // Nothing uses this cell yet. If nothing ever does, it becomes a gap.
+    Undecided,
+
+    // The cell has to be a gap, e.g. because a LOLO needed it to be done.
+    Gap,
+
+    // The cell has this letter in the puzzle, and the generating solution has or hasn't blackened it yet.
+    Cell(char, bool),
+}
+
+/// A puzzle being generated, along with the solution that generates it.
+#[derive(Clone)]
+struct Plan {
+    grid: Grid<Slot>,
+    moves: Vec<Move>,
+
+    // Letters that TA has already blackened all of. Adding any more of them would have made that TA incomplete.
+    banned_letters: Vec<char>,
+
+    keyword_count: usize,
+}
+
+impl Plan {
+    fn new(width: usize, height: usize) -> Plan {
+        Plan {
+            grid: Grid::new(width, height, &Slot::Undecided),
+            moves: vec![],
+            banned_letters: vec![],
+            keyword_count: 0,
+        }
+    }
+
+    /// Returns the cell one step from `rc` in the given direction, if it is on the board.
+    fn step(&self, rc: &RC, direction: Direction) -> Option<RC> {
+        self.grid.walk(rc, direction).next().map(|(rc, _)| rc)
+    }
+
+    fn cells_where(&self, predicate: impl Fn(&Slot) -> bool) -> Vec<RC> {
+        self.grid
+            .enumerate_row_col()
+            .filter(|(_, slot)| predicate(slot))
+            .map(|(rc, _)| rc)
+            .collect()
+    }
+
+    fn undecided_cells(&self) -> Vec<RC> {
+        self.cells_where(|slot| *slot == Slot::Undecided)
+    }
+
+    /// Gets the cells that are in the puzzle but that the solution hasn't blackened yet, like conductors.
+    fn pending_cells(&self) -> Vec<RC> {
+        self.cells_where(|slot| matches!(slot, Slot::Cell(_, false)))
+    }
+
+    /// Returns whether a keyword could execute by blackening this cell.
+    fn can_blacken(&self, rc: &RC) -> bool {
+        matches!(self.grid[rc], Slot::Undecided | Slot::Cell(_, false))
+    }
+
+    /// Formats the puzzle as text for `Board::new`.
+    fn to_puzzle(&self) -> String {
+        self.grid
+            .map(|slot| match slot {
+                Slot::Undecided | Slot::Gap => GAP_LETTER,
+                Slot::Cell(letter, _) => *letter,
+            })
+            .to_string()
+    }
+}
+
+/// One cell visited while gathering a keyword.
+enum PathCell {
+    // A cell with the letter at this index in the keyword.
+    Letter(RC, usize),
+
+    // A conductor that the path turns at.
+    Conductor(RC),
+}
+
+/// Gets the keywords that puzzles can be generated with: the keywords from the game.
+fn standard_keywords() -> Vec<String> {
+    KeywordRegistry::standard()
+        .iter()
+        .map(|keyword| keyword.letters().to_string())
+        .collect()
+}
+
+/// Which mechanics the generator can use, and how big a puzzle to make.
+pub struct GeneratorOptions {
+    /// The number of columns in the puzzle.
+    pub width: usize,
+
+    /// The number of rows in the puzzle.
+    pub height: usize,
+
+    /// Generating with the same seed and options always produces the same puzzle.
+    pub seed: u64,
+
+    /// The keywords that can be used. Only the keywords from the game, the ones in `KeywordRegistry::standard`, are
+    /// supported.
+    pub keywords: Vec<String>,
+
+    /// Whether keywords can turn at conductors.
+    pub conductors: bool,
+
+    /// Whether keyword letters can start out as wildcards.
+    pub wildcards: bool,
+}
+
+impl GeneratorOptions {
+    /// Creates options for a puzzle of the given size that can use every mechanic.
+    pub fn new(width: usize, height: usize, seed: u64) -> GeneratorOptions {
+        GeneratorOptions {
+            width,
+            height,
+            seed,
+            keywords: standard_keywords(),
+            conductors: true,
+            wildcards: true,
+        }
+    }
+}
+
+/// A generated puzzle and a solution to it.
+#[wasm_bindgen]
+pub struct GeneratedPuzzle {
+    puzzle: String,
+    solution: Vec<Move>,
+}
+
+#[wasm_bindgen]
+impl GeneratedPuzzle {
+    /// Gets the puzzle as text that can be passed to `Board::new`.
+    pub fn get_puzzle(&self) -> String {
+        self.puzzle.clone()
+    }
+
+    /// Gets the solution as text that can be passed to `Board::import_moves`.
+    pub fn get_solution(&self) -> String {
+        self.solution
+            .iter()
+            .map(|mv| mv.to_string())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+impl GeneratedPuzzle {
+    /// Gets the moves of the solution that the puzzle was generated from.
+    pub fn solution(&self) -> &[Move] {
+        &self.solution
+    }
+}
+
+/// Generates a random puzzle of the given size with every mechanic allowed. See `generate`.
+#[wasm_bindgen]
+pub fn generate_puzzle(width: usize, height: usize, seed: u32) -> Result<GeneratedPuzzle, String> {
+    generate(&GeneratorOptions::new(width, height, seed as u64))
+}
+
+/// Generates a random puzzle by making up a random solution and filling in the board with whatever that solution needs.
+/// Cells that the solution doesn't need become gaps. The solution is checked against the rules before it is returned,
+/// so the puzzle is always solvable, but it may have other solutions too.
+pub fn generate(options: &GeneratorOptions) -> Result<GeneratedPuzzle, String> {
+    if options.keywords.is_empty() {
+        return Err("At least one keyword is needed to generate a puzzle!".to_string());
+    }
+
+    if options.width == 0 || options.height == 0 {
+        return Err(format!(
+            "Can't generate a {}x{} puzzle with no cells!",
+            options.width, options.height
+        ));
+    }
+
+    match options.width.checked_mul(options.height) {
+        Some(cells) if cells <= MAX_CELLS => {}
+        _ => {
+            return Err(format!(
+                "Can't generate a {}x{} puzzle with more than {} cells!",
+                options.width, options.height, MAX_CELLS
+            ))
+        }
+    }
+
+    // Every keyword has to fit in a straight line somewhere.
+    let shortest = options.keywords.iter().map(String::len).min().unwrap();
+    if options.width.max(options.height) < shortest {
+        return Err(format!(
+            "A {}x{} puzzle is too small to fit any of the keywords!",
+            options.width, options.height
+        ));
+    }
+
+    let supported = standard_keywords();
+    for keyword in options.keywords.iter() {
+        if !supported.contains(keyword) {
+            return Err(format!(
+                "Can't generate puzzles with the keyword {}!",
+                keyword
+            ));
+        }
+    }
+
+    if options.keywords.iter().all(|keyword| keyword == "BE") {
+        return Err("BE needs another keyword to fill in a letter for!".to_string());
+    }
+
+    let mut generator = Generator {
+        options,
+        rng: Rng::new(options.seed),
+    };
+
+    for _ in 0..MAX_ATTEMPTS {
+        if let Some(plan) = generator.generate_plan()? {
+            let puzzle = plan.to_puzzle();
+
+            // Double check the solution with the real rules.
+            let mut board = Board::new(&puzzle)?;
+            for mv in plan.moves.iter() {
+                board.make_move(mv);
+            }
+
+            if board.check_solution() == SolutionResult::Correct {
+                return Ok(GeneratedPuzzle {
+                    puzzle,
+                    solution: plan.moves,
+                });
+            }
+        }
+    }
+
+    Err(format!(
+        "Couldn't generate a {}x{} puzzle with these options!",
+        options.width, options.height
+    ))
+}
+
+struct Generator<'o> {
+    options: &'o GeneratorOptions,
+    rng: Rng,
+}
+
+impl Generator<'_> {
+    /// Adds keywords until the board is full, then makes sure every cell the solution used gets blackened. Returns
+    /// `None` if that doesn't work out.
+    fn generate_plan(&mut self) -> Result<Option<Plan>, String> {
+        let mut plan = Plan::new(self.options.width, self.options.height);
+
+        let mut failures = 0;
+        while failures < MAX_FAILURES {
+            if self.try_add_keyword(&mut plan)? {
+                failures = 0;
+            } else {
+                failures += 1;
+            }
+        }
+
+        // Conductors aren't blackened when gathering, so some other keyword has to take care of them.
+        failures = 0;
+        while !plan.pending_cells().is_empty() && failures < MAX_FAILURES {
+            if !self.try_add_keyword(&mut plan)? {
+                failures += 1;
+            }
+        }
+
+        if plan.keyword_count > 0 && plan.pending_cells().is_empty() {
+            Ok(Some(plan))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Tries to add a random keyword to the plan, both gathering it and executing it. Leaves the plan unchanged if
+    /// there is no room. Fails if the keyword is one the generator doesn't know how to execute.
+    fn try_add_keyword(&mut self, plan: &mut Plan) -> Result<bool, String> {
+        let keyword = self.rng.pick(&self.options.keywords).unwrap().clone();
+
+        let mut next = plan.clone();
+        if !self.gather(&mut next, &keyword, false) {
+            return Ok(false);
+        }
+
+        let did_execute = self.execute(&mut next, &keyword)?;
+        if did_execute {
+            *plan = next;
+        }
+
+        Ok(did_execute)
+    }
+
+    /// Picks a random filler letter for a cell that will only ever be blackened.
+    fn filler_letter(&mut self, plan: &Plan) -> char {
+        let letters: Vec<char> = FILLER_LETTERS
+            .chars()
+            .filter(|letter| !plan.banned_letters.contains(letter))
+            .collect();
+
+        if self.rng.chance(10) {
+            BLANK_LETTER
+        } else {
+            *self.rng.pick(&letters).unwrap_or(&BLANK_LETTER)
+        }
+    }
+
+    /// Blackens a cell as part of executing a keyword, deciding its letter if it hasn't been yet.
+    /// Fails if the cell is a gap or was already blackened, which would be a bug in how the keyword was executed.
+    fn blacken(&mut self, plan: &mut Plan, rc: &RC) -> Result<(), String> {
+        let letter = match plan.grid[rc] {
+            Slot::Cell(letter, false) => letter,
+            Slot::Undecided => self.filler_letter(plan),
+            _ => {
+                return Err(format!(
+                    "Generated a move to blacken {:?}, which can't be blackened!",
+                    rc
+                ))
+            }
+        };
+
+        plan.grid[rc] = Slot::Cell(letter, true);
+        plan.moves.push(Move::Blacken(rc.clone()));
+        Ok(())
+    }
+
+    /// Picks a cell for a keyword to blacken, preferring ones that still need to be blackened.
+    fn pick_target(&mut self, plan: &Plan) -> Option<RC> {
+        let pending = plan.pending_cells();
+        if !pending.is_empty() {
+            return self.rng.pick(&pending).cloned();
+        }
+
+        self.rng.pick(&plan.undecided_cells()).cloned()
+    }
+
+    /// Finds a random path through undecided cells to gather the keyword along, possibly turning at a conductor.
+    fn find_path(&mut self, plan: &Plan, keyword: &str) -> Option<Vec<PathCell>> {
+        let len = keyword.len();
+        let undecided = plan.undecided_cells();
+
+        for _ in 0..MAX_FAILURES {
+            let start = self.rng.pick(&undecided)?.clone();
+            let mut direction = *self.rng.pick(&Direction::ORTHOGONAL).unwrap();
+
+            // Turn at a conductor between two of the letters, going off perpendicular to the original direction.
+            let turn_before = if self.options.conductors && self.rng.chance(30) {
+                Some(1 + self.rng.below(len - 1))
+            } else {
+                None
+            };
+
+            let mut path = vec![PathCell::Letter(start.clone(), 0)];
+            let mut current = Some(start);
+            for i in 1..len {
+                if turn_before == Some(i) {
+                    current = current.and_then(|rc| plan.step(&rc, direction));
+                    if let Some(rc) = &current {
+                        path.push(PathCell::Conductor(rc.clone()));
+                    }
+
+                    let turns: Vec<Direction> = Direction::ORTHOGONAL
+                        .iter()
+                        .filter(|turn| **turn != direction && **turn != direction.opposite())
+                        .copied()
+                        .collect();
+                    direction = *self.rng.pick(&turns).unwrap();
+                }
+
+                current = current.and_then(|rc| plan.step(&rc, direction));
+                if let Some(rc) = &current {
+                    path.push(PathCell::Letter(rc.clone(), i));
+                }
+            }
+
+            let is_open = current.is_some()
+                && path.iter().all(|cell| match cell {
+                    PathCell::Letter(rc, _) | PathCell::Conductor(rc) => {
+                        plan.grid[rc] == Slot::Undecided
+                    }
+                });
+
+            if is_open {
+                return Some(path);
+            }
+        }
+
+        None
+    }
+
+    /// Gathers a keyword along a new path. If `fill_blank` is set, one of its letters starts out blank and is filled
+    /// in first, which is how BE is executed.
+    fn gather(&mut self, plan: &mut Plan, keyword: &str, fill_blank: bool) -> bool {
+        let path = match self.find_path(plan, keyword) {
+            Some(path) => path,
+            None => return false,
+        };
+
+        let letters: Vec<char> = keyword.chars().collect();
+        let blank_index = if fill_blank {
+            Some(self.rng.below(letters.len()))
+        } else {
+            None
+        };
+
+        let mut changes = vec![];
+        let mut gather_moves = vec![];
+        for cell in path.iter() {
+            match cell {
+                PathCell::Letter(rc, i) => {
+                    let letter = letters[*i];
+                    let puzzle_letter = if blank_index == Some(*i) {
+                        changes.insert(0, Move::ChangeLetter(rc.clone(), letter));
+                        BLANK_LETTER
+                    } else if self.options.wildcards && self.rng.chance(15) {
+                        changes.push(Move::ChangeLetter(rc.clone(), letter));
+                        WILDCARD_LETTER
+                    } else {
+                        letter
+                    };
+
+                    plan.grid[rc] = Slot::Cell(puzzle_letter, true);
+                    gather_moves.push(Move::Blacken(rc.clone()));
+                }
+                PathCell::Conductor(rc) => {
+                    plan.grid[rc] = Slot::Cell(CONDUCTOR_LETTER, false);
+                    gather_moves.push(Move::MarkPath(rc.clone()));
+                }
+            }
+        }
+
+        plan.moves.append(&mut changes);
+        plan.moves.append(&mut gather_moves);
+        plan.keyword_count += 1;
+        true
+    }
+
+    /// Adds the moves that execute a keyword that was just gathered. Every keyword in `KeywordRegistry::standard` needs
+    /// its own way of being executed here. Fails for any other keyword.
+    fn execute(&mut self, plan: &mut Plan, keyword: &str) -> Result<bool, String> {
+        match keyword {
+            "LOK" => self.execute_lok(plan),
+            "TLAK" => self.execute_tlak(plan),
+            "TA" => Ok(self.execute_ta(plan)),
+            "BE" => self.execute_be(plan),
+            "LOLO" => self.execute_lolo(plan),
+            _ => Err(format!(
+                "Can't generate puzzles with the keyword {}!",
+                keyword
+            )),
+        }
+    }
+
+    fn execute_lok(&mut self, plan: &mut Plan) -> Result<bool, String> {
+        match self.pick_target(plan) {
+            Some(rc) => {
+                self.blacken(plan, &rc)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn execute_tlak(&mut self, plan: &mut Plan) -> Result<bool, String> {
+        for _ in 0..MAX_FAILURES {
+            let first = match self.pick_target(plan) {
+                Some(rc) => rc,
+                None => return Ok(false),
+            };
+
+            let neighbors: Vec<RC> = plan
+                .grid
+                .neighbors(&first)
+                .map(|(rc, _)| rc)
+                .filter(|rc| plan.can_blacken(rc))
+                .collect();
+
+            if let Some(second) = self.rng.pick(&neighbors).cloned() {
+                self.blacken(plan, &first)?;
+                self.blacken(plan, &second)?;
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    fn execute_ta(&mut self, plan: &mut Plan) -> bool {
+        let letters: Vec<char> = FILLER_LETTERS
+            .chars()
+            .filter(|letter| !plan.banned_letters.contains(letter))
+            .collect();
+        let letter = match self.rng.pick(&letters) {
+            Some(letter) => *letter,
+            None => return false,
+        };
+
+        let mut undecided = plan.undecided_cells();
+        if undecided.is_empty() {
+            return false;
+        }
+
+        let count = 1 + self.rng.below(3.min(undecided.len()));
+        for _ in 0..count {
+            let rc = undecided.remove(self.rng.below(undecided.len()));
+            plan.grid[&rc] = Slot::Cell(letter, true);
+            plan.moves.push(Move::Blacken(rc));
+        }
+
+        plan.banned_letters.push(letter);
+        true
+    }
+
+    fn execute_be(&mut self, plan: &mut Plan) -> Result<bool, String> {
+        let keywords: Vec<String> = self
+            .options
+            .keywords
+            .iter()
+            .filter(|keyword| *keyword != "BE")
+            .cloned()
+            .collect();
+
+        // The blank that BE fills in is used right away to gather the next keyword, which then has to be executed.
+        let keyword = self.rng.pick(&keywords).unwrap().clone();
+        if !self.gather(plan, &keyword, true) {
+            return Ok(false);
+        }
+
+        self.execute(plan, &keyword)
+    }
+
+    fn execute_lolo(&mut self, plan: &mut Plan) -> Result<bool, String> {
+        let anchor = match self.pick_target(plan) {
+            Some(rc) => rc,
+            None => return Ok(false),
+        };
+
+        self.blacken(plan, &anchor)?;
+
+        // Every cell on the diagonal has to be done when the LOLO is, so either blacken each one or make it a gap.
+        for direction in [Direction::UpRight, Direction::DownLeft] {
+            let diagonal: Vec<RC> = plan
+                .grid
+                .walk(&anchor, direction)
+                .map(|(rc, _)| rc)
+                .collect();
+            for rc in diagonal {
+                match plan.grid[&rc] {
+                    Slot::Undecided => {
+                        if self.rng.chance(50) {
+                            self.blacken(plan, &rc)?;
+                        } else {
+                            plan.grid[&rc] = Slot::Gap;
+                        }
+                    }
+                    Slot::Cell(_, false) => self.blacken(plan, &rc)?,
+                    Slot::Gap | Slot::Cell(_, true) => {}
+                }
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_generated(options: &GeneratorOptions) -> GeneratedPuzzle {
+        let generated = generate(options).unwrap();
+        let mut board = Board::new(&generated.get_puzzle()).unwrap();
+        board.import_moves(&generated.get_solution()).unwrap();
+        assert_eq!(board.check_solution(), SolutionResult::Correct);
+        assert_eq!(board.width() as usize, options.width);
+        assert_eq!(board.height() as usize, options.height);
+        generated
+    }
+
+    #[test]
+    fn generated_puzzles_are_solvable() {
+        for seed in 0..50 {
+            check_generated(&GeneratorOptions::new(6, 5, seed));
+        }
+    }
+
+    #[test]
+    fn same_seed_same_puzzle() {
+        let first = generate(&GeneratorOptions::new(5, 5, 1234)).unwrap();
+        let second = generate(&GeneratorOptions::new(5, 5, 1234)).unwrap();
+        assert_eq!(first.get_puzzle(), second.get_puzzle());
+        assert_eq!(first.solution(), second.solution());
+    }
+
+    #[test]
+    fn different_seeds_differ() {
+        let first = generate(&GeneratorOptions::new(5, 5, 1)).unwrap();
+        let second = generate(&GeneratorOptions::new(5, 5, 2)).unwrap();
+        assert_ne!(first.get_puzzle(), second.get_puzzle());
+    }
+
+    #[test]
+    fn restricted_mechanics() {
+        for seed in 0..20 {
+            let mut options = GeneratorOptions::new(5, 4, seed);
+            options.keywords = vec!["LOK".to_string(), "TA".to_string()];
+            options.conductors = false;
+            options.wildcards = false;
+
+            let generated = check_generated(&options);
+            let puzzle = generated.get_puzzle();
+            assert!(!puzzle.contains(CONDUCTOR_LETTER));
+            assert!(!puzzle.contains(WILDCARD_LETTER));
+            assert!(!puzzle.contains('B'));
+            assert!(!puzzle.contains('E'));
+        }
+    }
+
+    #[test]
+    fn every_mechanic_shows_up() {
+        let puzzles: String = (0..30)
+            .map(|seed| {
+                generate(&GeneratorOptions::new(7, 7, seed))
+                    .unwrap()
+                    .get_puzzle()
+            })
+            .collect();
+
+        for letter in [
+            CONDUCTOR_LETTER,
+            WILDCARD_LETTER,
+            BLANK_LETTER,
+            GAP_LETTER,
+            'B',
+            'E',
+        ] {
+            assert!(puzzles.contains(letter), "{} never generated", letter);
+        }
+    }
+
+    #[test]
+    fn every_standard_keyword_can_be_generated() {
+        let mut options = GeneratorOptions::new(6, 6, 0);
+        assert_eq!(options.keywords, ["LOK", "TLAK", "TA", "BE", "LOLO"]);
+
+        // BE always needs another keyword to fill in a letter for.
+        for keyword in standard_keywords() {
+            options.keywords = vec![keyword.clone(), "LOK".to_string()];
+            for seed in 0..5 {
+                options.seed = seed;
+                check_generated(&options);
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_options() {
+        assert!(generate(&GeneratorOptions::new(0, 0, 0)).is_err());
+        assert!(generate(&GeneratorOptions::new(2, 1, 0)).is_err());
+
+        let mut options = GeneratorOptions::new(5, 5, 0);
+        options.keywords = vec!["BE".to_string()];
+        assert!(generate(&options).is_err());
+
+        options.keywords = vec!["NOPE".to_string()];
+        assert!(generate(&options).is_err());
+    }
+
+    #[test]
+    fn invalid_sizes() {
+        assert!(generate(&GeneratorOptions::new(0, 5, 0)).is_err());
+        assert!(generate(&GeneratorOptions::new(5, 0, 0)).is_err());
+        assert!(generate(&GeneratorOptions::new(1, 1, 0)).is_err());
+        assert!(generate(&GeneratorOptions::new(65, 64, 0)).is_err());
+        assert!(generate(&GeneratorOptions::new(usize::MAX / 2, usize::MAX / 2, 0)).is_err());
+        assert!(generate(&GeneratorOptions::new(usize::MAX, 2, 0)).is_err());
+    }
+
+    #[test]
+    fn execute_unknown_keyword() {
+        let options = GeneratorOptions::new(5, 5, 0);
+        let mut generator = Generator {
+            options: &options,
+            rng: Rng::new(options.seed),
+        };
+
+        let mut plan = Plan::new(options.width, options.height);
+        assert!(generator.execute(&mut plan, "NOPE").is_err());
+    }
+
+    #[test]
+    fn blacken_twice() {
+        let options = GeneratorOptions::new(5, 5, 0);
+        let mut generator = Generator {
+            options: &options,
+            rng: Rng::new(options.seed),
+        };
+
+        let mut plan = Plan::new(options.width, options.height);
+        generator.blacken(&mut plan, &RC(0, 0)).unwrap();
+        assert!(generator.blacken(&mut plan, &RC(0, 0)).is_err());
+        assert_eq!(plan.moves, vec![Move::Blacken(RC(0, 0))]);
+
+        plan.grid[&RC(0, 1)] = Slot::Gap;
+        assert!(generator.blacken(&mut plan, &RC(0, 1)).is_err());
+    }
+}
@@ -1,8 +1,16 @@
+use std::fmt;
 use std::ops::{Deref, DerefMut, Index, IndexMut};
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "serde")]
+use std::convert::TryFrom;
 
 /// A row/column pair for indexing into the grid.
 /// Distinct from an x/y pair.
-#[derive(PartialEq, Clone, Debug)]
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct RC(pub usize, pub usize);
 
 /// An x/y pair for indexing into the grid.
@@ -10,12 +18,173 @@ pub struct RC(pub usize, pub usize);
 #[derive(PartialEq, Clone, Debug)]
 pub struct XY(pub usize, pub usize);
 
+/// A direction to walk in across a grid, one cell at a time.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
+}
+
+impl Direction {
+    /// The directions along rows and columns.
+    pub const ORTHOGONAL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    /// The directions along diagonals.
+    pub const DIAGONAL: [Direction; 4] = [
+        Direction::UpLeft,
+        Direction::UpRight,
+        Direction::DownLeft,
+        Direction::DownRight,
+    ];
+
+    /// The change in row and column from taking one step in this direction.
+    pub fn delta(&self) -> (isize, isize) {
+        match self {
+            Direction::Up => (-1, 0),
+            Direction::Down => (1, 0),
+            Direction::Left => (0, -1),
+            Direction::Right => (0, 1),
+            Direction::UpLeft => (-1, -1),
+            Direction::UpRight => (-1, 1),
+            Direction::DownLeft => (1, -1),
+            Direction::DownRight => (1, 1),
+        }
+    }
+
+    /// Tells if this direction is along a diagonal.
+    pub fn is_diagonal(&self) -> bool {
+        let (row, col) = self.delta();
+        row != 0 && col != 0
+    }
+
+    /// Gets the direction that goes back the way this one came.
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+            Direction::UpLeft => Direction::DownRight,
+            Direction::UpRight => Direction::DownLeft,
+            Direction::DownLeft => Direction::UpRight,
+            Direction::DownRight => Direction::UpLeft,
+        }
+    }
+
+    /// Gets the direction to walk in to get from one location to another in a
+    /// straight line, if they're on the same row, column, or diagonal. A
+    /// location has no direction to itself.
+    pub fn between(from: &RC, to: &RC) -> Option<Direction> {
+        let rows = to.0.abs_diff(from.0);
+        let cols = to.1.abs_diff(from.1);
+        if (rows == 0 && cols == 0) || (rows != 0 && cols != 0 && rows != cols) {
+            return None;
+        }
+
+        let delta = (
+            to.0.cmp(&from.0) as i8 as isize,
+            to.1.cmp(&from.1) as i8 as isize,
+        );
+        Direction::ORTHOGONAL
+            .iter()
+            .chain(Direction::DIAGONAL.iter())
+            .find(|direction| direction.delta() == delta)
+            .copied()
+    }
+}
+
+/// One of the eight ways to rotate or reflect a grid onto a grid of the same
+/// or swapped dimensions.
+#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
+pub enum Transform {
+    Identity,
+    RotateClockwise,
+    Rotate180,
+    RotateCounterclockwise,
+
+    /// Mirrors left and right.
+    FlipHorizontal,
+
+    /// Mirrors top and bottom.
+    FlipVertical,
+
+    /// Mirrors across the diagonal from the upper-left to the lower-right, so
+    /// rows become columns.
+    Transpose,
+
+    /// Mirrors across the diagonal from the lower-left to the upper-right.
+    AntiTranspose,
+}
+
+impl Transform {
+    /// Every transform, starting with `Identity`.
+    pub const ALL: [Transform; 8] = [
+        Transform::Identity,
+        Transform::RotateClockwise,
+        Transform::Rotate180,
+        Transform::RotateCounterclockwise,
+        Transform::FlipHorizontal,
+        Transform::FlipVertical,
+        Transform::Transpose,
+        Transform::AntiTranspose,
+    ];
+
+    /// Tells if the transform swaps the width and height of a grid.
+    pub fn swaps_dimensions(&self) -> bool {
+        match self {
+            Transform::Identity
+            | Transform::Rotate180
+            | Transform::FlipHorizontal
+            | Transform::FlipVertical => false,
+            Transform::RotateClockwise
+            | Transform::RotateCounterclockwise
+            | Transform::Transpose
+            | Transform::AntiTranspose => true,
+        }
+    }
+
+    /// Gets where a location in a grid of the given dimensions ends up after
+    /// the transform.
+    pub fn apply(&self, RC(row, col): &RC, width: usize, height: usize) -> RC {
+        let (row, col) = (*row, *col);
+        match self {
+            Transform::Identity => RC(row, col),
+            Transform::RotateClockwise => RC(col, height - 1 - row),
+            Transform::Rotate180 => RC(height - 1 - row, width - 1 - col),
+            Transform::RotateCounterclockwise => RC(width - 1 - col, row),
+            Transform::FlipHorizontal => RC(row, width - 1 - col),
+            Transform::FlipVertical => RC(height - 1 - row, col),
+            Transform::Transpose => RC(col, row),
+            Transform::AntiTranspose => RC(width - 1 - col, height - 1 - row),
+        }
+    }
+}
+
 /// A simple grid of user-defined objects.
 ///
 /// It dereferences to a slice of [`CellType`], so you can directly manipulate
 /// it via regular (mutable) slice methods. In addition, you can index
 /// into it by `(row, column)` pairs.
+///
+/// A grid with no rows also has no columns, and the other way around, so an
+/// empty grid is always 0x0.
+///
+/// With the `serde` feature, it serializes as its `width`, `height`, and
+/// `cells` in reading order.
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "GridData<CellType>"))]
 pub struct Grid<CellType>
 where
     CellType: Clone,
@@ -25,6 +194,61 @@ where
     cells: Vec<CellType>,
 }
 
+// What a grid is deserialized from, before checking that it has the right
+// number of cells for its size.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct GridData<CellType> {
+    width: usize,
+    height: usize,
+    cells: Vec<CellType>,
+}
+
+#[cfg(feature = "serde")]
+impl<CellType> TryFrom<GridData<CellType>> for Grid<CellType>
+where
+    CellType: Clone,
+{
+    type Error = String;
+
+    fn try_from(data: GridData<CellType>) -> Result<Self, Self::Error> {
+        if data.width == 0 && data.height != 0 {
+            return Err(format!(
+                "A grid with {} rows needs at least one column!",
+                data.height
+            ));
+        }
+
+        if data.height == 0 && data.width != 0 {
+            return Err(format!(
+                "A grid with {} columns needs at least one row!",
+                data.width
+            ));
+        }
+
+        let cell_count = data
+            .width
+            .checked_mul(data.height)
+            .ok_or_else(|| format!("A {}x{} grid is too big!", data.width, data.height))?;
+
+        if data.cells.len() != cell_count {
+            return Err(format!(
+                "A {}x{} grid needs {} cells, but has {}!",
+                data.width,
+                data.height,
+                cell_count,
+                data.cells.len()
+            ));
+        }
+
+        Ok(Grid {
+            width: data.width,
+            height: data.height,
+            cells: data.cells,
+        })
+    }
+}
+
 impl<CellType> Grid<CellType>
 where
     CellType: Clone,
@@ -44,12 +268,30 @@ where
         XY(index % self.width(), index / self.width())
     }
 
-    /// Create a blank grid with the given dimensions.
+    /// Create a blank grid with the given dimensions. If either one is 0, the
+    /// grid is 0x0. Panics if the grid would have more cells than fit in
+    /// memory.
     pub fn new(width: usize, height: usize, template: &CellType) -> Grid<CellType> {
-        Grid {
+        let cell_count = width
+            .checked_mul(height)
+            .unwrap_or_else(|| panic!("A {}x{} grid is too big", width, height));
+
+        let mut grid = Grid {
             width,
             height,
-            cells: vec![template.clone(); (width * height) as usize],
+            cells: vec![template.clone(); cell_count],
+        };
+        grid.normalize_empty();
+        grid
+    }
+
+    // Makes a grid with no rows or no columns 0x0, so there's only one way to
+    // be empty.
+    fn normalize_empty(&mut self) {
+        if self.width == 0 || self.height == 0 {
+            self.width = 0;
+            self.height = 0;
+            self.cells.clear();
         }
     }
 
@@ -61,8 +303,220 @@ where
         &mut self.cells
     }
 
-    pub fn enumerate_row_col(&self) -> GridRowColumnEnumerator<CellType> {
-        GridRowColumnEnumerator::new(&self)
+    pub fn enumerate_row_col(&self) -> GridRowColumnEnumerator<'_, CellType> {
+        GridRowColumnEnumerator::new(self)
+    }
+
+    /// Gets the cell at a location, or `None` if it's outside of the grid.
+    pub fn get(&self, rc: &RC) -> Option<&CellType> {
+        if self.contains(rc) {
+            Some(&self[rc])
+        } else {
+            None
+        }
+    }
+
+    /// Gets the cell at a location to change it, or `None` if it's outside of
+    /// the grid.
+    pub fn get_mut(&mut self, rc: &RC) -> Option<&mut CellType> {
+        if self.contains(rc) {
+            Some(&mut self[rc])
+        } else {
+            None
+        }
+    }
+
+    /// Tells if a location is inside the grid.
+    pub fn contains(&self, RC(row, col): &RC) -> bool {
+        *row < self.height && *col < self.width
+    }
+
+    /// Iterates over the cells next to a location along its row and column,
+    /// in the order of [`Direction::ORTHOGONAL`], skipping any that are
+    /// outside of the grid.
+    pub fn neighbors(&self, rc: &RC) -> impl Iterator<Item = (RC, &CellType)> + '_ {
+        let rc = rc.clone();
+        Direction::ORTHOGONAL
+            .iter()
+            .filter_map(move |direction| self.walk(&rc, *direction).next())
+    }
+
+    /// Iterates over all eight cells around a location, like `neighbors` but
+    /// followed by the ones on its diagonals.
+    pub fn neighbors_with_diagonals(&self, rc: &RC) -> impl Iterator<Item = (RC, &CellType)> + '_ {
+        let rc = rc.clone();
+        Direction::ORTHOGONAL
+            .iter()
+            .chain(Direction::DIAGONAL.iter())
+            .filter_map(move |direction| self.walk(&rc, *direction).next())
+    }
+
+    /// Gets the cells in a row, from left to right. Panics if the row is
+    /// outside of the grid.
+    pub fn row(&self, row: usize) -> &[CellType] {
+        assert!(row < self.height, "Row {} is outside of the grid", row);
+        &self.cells[row * self.width..(row + 1) * self.width]
+    }
+
+    /// Gets the cells in a row to change them. Panics if the row is outside of
+    /// the grid.
+    pub fn row_mut(&mut self, row: usize) -> &mut [CellType] {
+        assert!(row < self.height, "Row {} is outside of the grid", row);
+        &mut self.cells[row * self.width..(row + 1) * self.width]
+    }
+
+    /// Iterates over the rows, from top to bottom.
+    pub fn rows(&self) -> impl Iterator<Item = &[CellType]> {
+        (0..self.height).map(move |row| self.row(row))
+    }
+
+    /// Iterates over the cells in a column, from top to bottom. Panics if the
+    /// column is outside of the grid.
+    pub fn col(&self, col: usize) -> impl Iterator<Item = &CellType> {
+        assert!(col < self.width, "Col {} is outside of the grid", col);
+        self.cells.iter().skip(col).step_by(self.width)
+    }
+
+    /// Creates a grid of the same size, with each cell converted by `f`.
+    pub fn map<OtherType, F>(&self, f: F) -> Grid<OtherType>
+    where
+        OtherType: Clone,
+        F: FnMut(&CellType) -> OtherType,
+    {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    /// Changes the size of the grid. Cells keep their locations, cells that
+    /// are now outside of the grid are dropped, and new cells are copies of
+    /// `template`. If either dimension is 0, the grid becomes 0x0.
+    pub fn resize(&mut self, width: usize, height: usize, template: &CellType) {
+        let mut resized = Grid::new(width, height, template);
+        for row in 0..height.min(self.height) {
+            for col in 0..width.min(self.width) {
+                resized[&RC(row, col)] = self[&RC(row, col)].clone();
+            }
+        }
+
+        *self = resized;
+    }
+
+    /// Inserts a row of copies of `template` before the given row, moving the
+    /// rows below it down. Panics if `row` is greater than the height, or if
+    /// the grid is empty, since the row would have no columns. Use `resize` to
+    /// grow an empty grid.
+    pub fn insert_row(&mut self, row: usize, template: &CellType) {
+        assert!(row <= self.height, "Row {} is outside of the grid", row);
+        assert!(self.width > 0, "Can't insert a row into an empty grid");
+        let at = row * self.width;
+        self.cells
+            .splice(at..at, std::iter::repeat_n(template.clone(), self.width));
+        self.height += 1;
+    }
+
+    /// Inserts a column of copies of `template` before the given column,
+    /// moving the columns to its right over. Panics if `col` is greater than
+    /// the width, or if the grid is empty, since the column would have no
+    /// rows. Use `resize` to grow an empty grid.
+    pub fn insert_col(&mut self, col: usize, template: &CellType) {
+        assert!(col <= self.width, "Col {} is outside of the grid", col);
+        assert!(self.height > 0, "Can't insert a col into an empty grid");
+        for row in (0..self.height).rev() {
+            self.cells.insert(row * self.width + col, template.clone());
+        }
+        self.width += 1;
+    }
+
+    /// Removes a row, moving the rows below it up, and returns its cells.
+    /// Removing the last row leaves the grid 0x0. Panics if the row is outside
+    /// of the grid.
+    pub fn remove_row(&mut self, row: usize) -> Vec<CellType> {
+        assert!(row < self.height, "Row {} is outside of the grid", row);
+        let at = row * self.width;
+        let removed = self.cells.drain(at..at + self.width).collect();
+        self.height -= 1;
+        self.normalize_empty();
+        removed
+    }
+
+    /// Removes a column, moving the columns to its right over, and returns its
+    /// cells from top to bottom. Removing the last column leaves the grid 0x0.
+    /// Panics if the column is outside of the grid.
+    pub fn remove_col(&mut self, col: usize) -> Vec<CellType> {
+        assert!(col < self.width, "Col {} is outside of the grid", col);
+        let mut removed = vec![];
+        for row in (0..self.height).rev() {
+            removed.push(self.cells.remove(row * self.width + col));
+        }
+        removed.reverse();
+        self.width -= 1;
+        self.normalize_empty();
+        removed
+    }
+
+    /// Walks from a location in a straight line until the edge of the grid,
+    /// visiting each cell along the way, but not the one it starts from.
+    pub fn walk(&self, from: &RC, direction: Direction) -> GridWalk<'_, CellType> {
+        GridWalk {
+            grid: self,
+            current: from.clone(),
+            direction,
+        }
+    }
+
+    /// Creates a copy of the grid that is rotated or reflected.
+    pub fn transform(&self, transform: Transform) -> Grid<CellType> {
+        let (width, height) = if transform.swaps_dimensions() {
+            (self.height, self.width)
+        } else {
+            (self.width, self.height)
+        };
+
+        let mut cells: Vec<Option<CellType>> = vec![None; self.cells.len()];
+        for (rc, cell) in self.enumerate_row_col() {
+            let RC(row, col) = transform.apply(&rc, self.width, self.height);
+            cells[row * width + col] = Some(cell.clone());
+        }
+
+        Grid {
+            width,
+            height,
+            cells: cells.into_iter().map(Option::unwrap).collect(),
+        }
+    }
+
+    /// Creates a copy of the grid that is rotated a quarter turn clockwise.
+    pub fn rotate_clockwise(&self) -> Grid<CellType> {
+        self.transform(Transform::RotateClockwise)
+    }
+
+    /// Creates a copy of the grid that is rotated half a turn.
+    pub fn rotate_180(&self) -> Grid<CellType> {
+        self.transform(Transform::Rotate180)
+    }
+
+    /// Creates a copy of the grid that is rotated a quarter turn
+    /// counterclockwise.
+    pub fn rotate_counterclockwise(&self) -> Grid<CellType> {
+        self.transform(Transform::RotateCounterclockwise)
+    }
+
+    /// Creates a copy of the grid that is mirrored left to right.
+    pub fn flip_horizontal(&self) -> Grid<CellType> {
+        self.transform(Transform::FlipHorizontal)
+    }
+
+    /// Creates a copy of the grid that is mirrored top to bottom.
+    pub fn flip_vertical(&self) -> Grid<CellType> {
+        self.transform(Transform::FlipVertical)
+    }
+
+    /// Creates a copy of the grid with its rows and columns swapped.
+    pub fn transpose(&self) -> Grid<CellType> {
+        self.transform(Transform::Transpose)
     }
 }
 
@@ -72,7 +526,7 @@ where
 {
     type Output = CellType;
     fn index(&self, RC(row, col): &RC) -> &Self::Output {
-        &self.cells[(row * self.width + col) as usize]
+        &self.cells[row * self.width + col]
     }
 }
 
@@ -81,7 +535,7 @@ where
     CellType: Clone,
 {
     fn index_mut(&mut self, RC(row, col): &RC) -> &mut Self::Output {
-        &mut self.cells[(row * self.width + col) as usize]
+        &mut self.cells[row * self.width + col]
     }
 }
 
@@ -91,7 +545,7 @@ where
 {
     type Output = CellType;
     fn index(&self, XY(x, y): &XY) -> &Self::Output {
-        &self.cells[(*y * self.width + *x) as usize]
+        &self.cells[*y * self.width + *x]
     }
 }
 
@@ -100,7 +554,7 @@ where
     CellType: Clone,
 {
     fn index_mut(&mut self, XY(x, y): &XY) -> &mut Self::Output {
-        &mut self.cells[(*y * self.width + *x) as usize]
+        &mut self.cells[*y * self.width + *x]
     }
 }
 
@@ -123,6 +577,65 @@ where
     }
 }
 
+/// Parses a grid of characters with one line per row, e.g. a puzzle. Every row
+/// has to have the same number of characters.
+impl FromStr for Grid<char> {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut width = 0;
+        let mut height = 0;
+        let mut cells = vec![];
+        for line in text.lines() {
+            let row_width = line.chars().count();
+            if height == 0 {
+                width = row_width;
+            }
+
+            if row_width != width {
+                return Err(format!(
+                    "Row {} had {} cols, but needed to have {} cols to match the rows above it!",
+                    height, row_width, width
+                ));
+            }
+
+            cells.extend(line.chars());
+            height += 1;
+        }
+
+        if width == 0 && height > 0 {
+            return Err(format!(
+                "There are {} rows, but none of them have any cols!",
+                height
+            ));
+        }
+
+        Ok(Grid {
+            width,
+            height,
+            cells,
+        })
+    }
+}
+
+/// Formats a grid of characters with one line per row, the same way that
+/// `from_str` parses it.
+impl fmt::Display for Grid<char> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, row) in self.rows().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+
+            for ch in row {
+                write!(f, "{}", ch)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
 /// An enumerator that iterates low to high row number and low to high column number. Basically reading order.
 pub struct GridRowColumnEnumerator<'g, CellType>
 where
@@ -169,3 +682,287 @@ where
         ret
     }
 }
+
+/// An iterator over the cells in a straight line from some location. See
+/// [`Grid::walk`].
+pub struct GridWalk<'g, CellType>
+where
+    CellType: Clone,
+{
+    grid: &'g Grid<CellType>,
+    current: RC,
+    direction: Direction,
+}
+
+impl<'g, CellType> Iterator for GridWalk<'g, CellType>
+where
+    CellType: Clone,
+{
+    type Item = (RC, &'g CellType);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (row_delta, col_delta) = self.direction.delta();
+        let row = self.current.0.checked_add_signed(row_delta)?;
+        let col = self.current.1.checked_add_signed(col_delta)?;
+        if row >= self.grid.height() || col >= self.grid.width() {
+            return None;
+        }
+
+        self.current = RC(row, col);
+        Some((self.current.clone(), &self.grid[&self.current]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A 3x2 grid numbered in reading order:
+    // 0 1 2
+    // 3 4 5
+    fn numbered() -> Grid<usize> {
+        let mut grid = Grid::new(3, 2, &0);
+        for (index, cell) in grid.iter_mut().enumerate() {
+            *cell = index;
+        }
+        grid
+    }
+
+    #[test]
+    fn transforms() {
+        let grid = numbered();
+        let rotated = grid.rotate_clockwise();
+        assert_eq!((rotated.width(), rotated.height()), (2, 3));
+        assert_eq!(rotated.cells(), &vec![3, 0, 4, 1, 5, 2]);
+
+        assert_eq!(grid.rotate_180().cells(), &vec![5, 4, 3, 2, 1, 0]);
+        assert_eq!(
+            grid.rotate_counterclockwise().cells(),
+            &vec![2, 5, 1, 4, 0, 3]
+        );
+        assert_eq!(grid.flip_horizontal().cells(), &vec![2, 1, 0, 5, 4, 3]);
+        assert_eq!(grid.flip_vertical().cells(), &vec![3, 4, 5, 0, 1, 2]);
+        assert_eq!(grid.transpose().cells(), &vec![0, 3, 1, 4, 2, 5]);
+        assert_eq!(
+            grid.transform(Transform::AntiTranspose).cells(),
+            &vec![5, 2, 4, 1, 3, 0]
+        );
+    }
+
+    #[test]
+    fn transform_apply_matches_grid() {
+        let grid = numbered();
+        for transform in Transform::ALL.iter() {
+            let transformed = grid.transform(*transform);
+            for (rc, cell) in grid.enumerate_row_col() {
+                let moved = transform.apply(&rc, grid.width(), grid.height());
+                assert_eq!(transformed[&moved], *cell, "{:?}", transform);
+            }
+        }
+    }
+
+    #[test]
+    fn four_rotations_are_identity() {
+        let grid = numbered();
+        let rotated = grid
+            .rotate_clockwise()
+            .rotate_clockwise()
+            .rotate_clockwise()
+            .rotate_clockwise();
+        assert_eq!((rotated.width(), rotated.height()), (3, 2));
+        assert_eq!(rotated.cells(), grid.cells());
+    }
+
+    #[test]
+    fn walk() {
+        let grid = numbered();
+        let walked = |from: RC, direction| -> Vec<usize> {
+            grid.walk(&from, direction).map(|(_, cell)| *cell).collect()
+        };
+
+        assert_eq!(walked(RC(0, 0), Direction::Right), vec![1, 2]);
+        assert_eq!(walked(RC(1, 2), Direction::Left), vec![4, 3]);
+        assert_eq!(walked(RC(0, 1), Direction::Down), vec![4]);
+        assert!(walked(RC(0, 1), Direction::Up).is_empty());
+        assert_eq!(walked(RC(1, 0), Direction::UpRight), vec![1]);
+        assert_eq!(walked(RC(0, 2), Direction::DownLeft), vec![4]);
+        assert!(walked(RC(1, 2), Direction::DownRight).is_empty());
+
+        let rcs: Vec<RC> = grid
+            .walk(&RC(0, 0), Direction::DownRight)
+            .map(|(rc, _)| rc)
+            .collect();
+        assert_eq!(rcs, vec![RC(1, 1)]);
+    }
+
+    #[test]
+    fn direction_between() {
+        assert_eq!(Direction::between(&RC(2, 2), &RC(2, 2)), None);
+        assert_eq!(
+            Direction::between(&RC(2, 2), &RC(0, 2)),
+            Some(Direction::Up)
+        );
+        assert_eq!(
+            Direction::between(&RC(2, 2), &RC(2, 5)),
+            Some(Direction::Right)
+        );
+        assert_eq!(
+            Direction::between(&RC(2, 2), &RC(4, 0)),
+            Some(Direction::DownLeft)
+        );
+        assert_eq!(
+            Direction::between(&RC(2, 2), &RC(0, 0)),
+            Some(Direction::UpLeft)
+        );
+        assert_eq!(Direction::between(&RC(2, 2), &RC(3, 4)), None);
+
+        for direction in Direction::ORTHOGONAL
+            .iter()
+            .chain(Direction::DIAGONAL.iter())
+        {
+            let (row, col) = direction.delta();
+            let (opposite_row, opposite_col) = direction.opposite().delta();
+            assert_eq!((row + opposite_row, col + opposite_col), (0, 0));
+            assert_eq!(
+                direction.is_diagonal(),
+                Direction::DIAGONAL.contains(direction)
+            );
+        }
+    }
+
+    #[test]
+    fn checked_access() {
+        let mut grid = numbered();
+        assert_eq!(grid.get(&RC(1, 2)), Some(&5));
+        assert_eq!(grid.get(&RC(2, 0)), None);
+        assert_eq!(grid.get(&RC(0, 3)), None);
+
+        *grid.get_mut(&RC(0, 1)).unwrap() = 9;
+        assert_eq!(grid[&RC(0, 1)], 9);
+        assert!(grid.get_mut(&RC(5, 5)).is_none());
+    }
+
+    #[test]
+    fn neighbors() {
+        let grid = numbered();
+        let cells = |neighbors: Vec<(RC, &usize)>| -> Vec<usize> {
+            neighbors.into_iter().map(|(_, cell)| *cell).collect()
+        };
+
+        assert_eq!(cells(grid.neighbors(&RC(0, 1)).collect()), vec![4, 0, 2]);
+        assert_eq!(cells(grid.neighbors(&RC(1, 0)).collect()), vec![0, 4]);
+        assert_eq!(
+            cells(grid.neighbors_with_diagonals(&RC(1, 1)).collect()),
+            vec![1, 3, 5, 0, 2]
+        );
+        assert_eq!(
+            grid.neighbors_with_diagonals(&RC(0, 0))
+                .map(|(rc, _)| rc)
+                .collect::<Vec<RC>>(),
+            vec![RC(1, 0), RC(0, 1), RC(1, 1)]
+        );
+    }
+
+    #[test]
+    fn rows_and_cols() {
+        let mut grid = numbered();
+        assert_eq!(grid.row(1), &[3, 4, 5]);
+        assert_eq!(
+            grid.rows().collect::<Vec<&[usize]>>(),
+            vec![&[0, 1, 2], &[3, 4, 5]]
+        );
+        assert_eq!(grid.col(2).copied().collect::<Vec<usize>>(), vec![2, 5]);
+
+        grid.row_mut(0).reverse();
+        assert_eq!(grid.cells(), &vec![2, 1, 0, 3, 4, 5]);
+    }
+
+    #[test]
+    fn map() {
+        let grid = numbered().map(|cell| cell % 2 == 0);
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.row(0), &[true, false, true]);
+    }
+
+    #[test]
+    fn parse_and_display() {
+        let grid: Grid<char> = "LOK_\n-TA-".parse().unwrap();
+        assert_eq!((grid.width(), grid.height()), (4, 2));
+        assert_eq!(grid[&RC(1, 2)], 'A');
+        assert_eq!(grid.to_string(), "LOK_\n-TA-");
+
+        assert_eq!(
+            "LOK\nLO".parse::<Grid<char>>().err().unwrap(),
+            "Row 1 had 2 cols, but needed to have 3 cols to match the rows above it!"
+        );
+
+        assert!("\n\n".parse::<Grid<char>>().is_err());
+
+        let empty: Grid<char> = "".parse().unwrap();
+        assert_eq!((empty.width(), empty.height()), (0, 0));
+        assert_eq!(empty.to_string(), "");
+    }
+
+    #[test]
+    fn resize() {
+        let mut grid = numbered();
+        grid.resize(2, 3, &9);
+        assert_eq!((grid.width(), grid.height()), (2, 3));
+        assert_eq!(grid.cells(), &vec![0, 1, 3, 4, 9, 9]);
+    }
+
+    #[test]
+    fn resize_to_empty() {
+        let mut grid = numbered();
+        grid.resize(0, 4, &9);
+        assert_eq!((grid.width(), grid.height()), (0, 0));
+        assert!(grid.cells().is_empty());
+
+        grid.resize(2, 1, &9);
+        assert_eq!(grid.cells(), &vec![9, 9]);
+
+        let grid = Grid::new(3, 0, &0);
+        assert_eq!((grid.width(), grid.height()), (0, 0));
+    }
+
+    #[test]
+    fn remove_down_to_empty() {
+        let mut grid = numbered();
+        for _ in 0..3 {
+            grid.remove_col(0);
+        }
+        assert_eq!((grid.width(), grid.height()), (0, 0));
+        assert!(grid.rows().next().is_none());
+
+        let mut grid = numbered();
+        grid.remove_row(1);
+        grid.remove_row(0);
+        assert_eq!((grid.width(), grid.height()), (0, 0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn insert_row_into_empty() {
+        let mut grid = Grid::new(0, 0, &0);
+        grid.insert_row(0, &1);
+    }
+
+    #[test]
+    fn insert_and_remove() {
+        let mut grid = numbered();
+        grid.insert_row(1, &7);
+        assert_eq!(grid.height(), 3);
+        assert_eq!(grid.cells(), &vec![0, 1, 2, 7, 7, 7, 3, 4, 5]);
+
+        grid.insert_col(3, &8);
+        assert_eq!(grid.width(), 4);
+        assert_eq!(grid.col(3).copied().collect::<Vec<usize>>(), vec![8, 8, 8]);
+        assert_eq!(grid.row(2), &[3, 4, 5, 8]);
+
+        assert_eq!(grid.remove_col(0), vec![0, 7, 3]);
+        assert_eq!(grid.remove_row(1), vec![7, 7, 8]);
+        assert_eq!(grid.remove_col(2), vec![8, 8]);
+        assert_eq!((grid.width(), grid.height()), (2, 2));
+        assert_eq!(grid.cells(), &vec![1, 2, 4, 5]);
+    }
+}
@@ -0,0 +1,92 @@
+/// Identifies a node in a [`HistoryTree`].
+pub type NodeId = usize;
+
+/// The root of every [`HistoryTree`], which represents the state before any items were added.
+pub const ROOT: NodeId = 0;
+
+struct Node<T> {
+    item: Option<T>,
+    children: Vec<NodeId>,
+
+    // The child that was most recently visited, which is the one to follow when redoing.
+    active_child: Option<usize>,
+}
+
+/// A tree of items, such as moves, where each path from the root is one possible history. Adding an item after going
+/// back to an earlier point creates a new branch instead of discarding what came after.
+pub struct HistoryTree<T>
+where
+    T: PartialEq,
+{
+    nodes: Vec<Node<T>>,
+}
+
+impl<T> HistoryTree<T>
+where
+    T: PartialEq,
+{
+    /// Creates a tree that contains only the root.
+    pub fn new() -> HistoryTree<T> {
+        HistoryTree {
+            nodes: vec![Node {
+                item: None,
+                children: vec![],
+                active_child: None,
+            }],
+        }
+    }
+
+    /// Adds an item as a child of `parent` and makes it the active branch there. If an equal item is already a child of
+    /// `parent`, that branch is reused instead. Returns the node holding the item.
+    pub fn push(&mut self, parent: NodeId, item: T) -> NodeId {
+        let existing_branch = self.nodes[parent]
+            .children
+            .iter()
+            .position(|child| self.nodes[*child].item.as_ref() == Some(&item));
+
+        let branch = existing_branch.unwrap_or_else(|| {
+            let id = self.nodes.len();
+            self.nodes.push(Node {
+                item: Some(item),
+                children: vec![],
+                active_child: None,
+            });
+            self.nodes[parent].children.push(id);
+            self.nodes[parent].children.len() - 1
+        });
+
+        self.nodes[parent].active_child = Some(branch);
+        self.nodes[parent].children[branch]
+    }
+
+    /// Gets the item held in a node. The root doesn't hold an item.
+    pub fn get(&self, id: NodeId) -> Option<&T> {
+        self.nodes[id].item.as_ref()
+    }
+
+    /// Gets the children of a node, i.e. the branches that continue from it, in the order they were created.
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        &self.nodes[id].children
+    }
+
+    /// Gets the index among the node's children of the branch that was most recently visited, if any.
+    pub fn active_branch(&self, id: NodeId) -> Option<usize> {
+        self.nodes[id].active_child
+    }
+
+    /// Gets the child on the branch that was most recently visited, if any.
+    pub fn active_child(&self, id: NodeId) -> Option<NodeId> {
+        self.active_branch(id)
+            .map(|branch| self.nodes[id].children[branch])
+    }
+
+    /// Makes a different child the active branch. Returns false if there is no such branch.
+    pub fn set_active_branch(&mut self, id: NodeId, branch: usize) -> bool {
+        if branch >= self.nodes[id].children.len() {
+            return false;
+        }
+
+        self.nodes[id].active_child = Some(branch);
+        true
+    }
+}
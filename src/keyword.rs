@@ -0,0 +1,514 @@
+use crate::grid::{Grid, RC};
+use crate::{Board, BoardCell, BoardGrid, Move, MoveError, Simulation, BLANK_LETTER, ME};
+
+/// Identifies a keyword within a [`KeywordRegistry`].
+pub type KeywordId = usize;
+
+/// How far the player has gotten in executing a keyword. Each keyword decides which of these it moves through.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ExecutionProgress {
+    // The keyword was just gathered, and the player hasn't done anything to execute it yet.
+    Started,
+
+    // The player chose a cell that the rest of the execution is relative to.
+    ChoseCell(RC),
+
+    // The player chose a letter that the rest of the execution has to match.
+    ChoseLetter(char),
+
+    // The player did everything the keyword asked for.
+    Finished,
+}
+
+/// The rules for one keyword: which cells can be gathered to spell it, what the player is expected to do once it has
+/// been gathered, and when they are done doing that.
+pub trait Keyword {
+    /// The letters that spell the keyword.
+    fn letters(&self) -> &'static str;
+
+    /// Returns whether `rc` can be gathered next for this keyword, after the cells targeted by `keyword_moves`. By
+    /// default, the cells have to be connected as described in `is_connected_for_keyword`.
+    fn can_gather(&self, grid: &Grid<BoardCell>, keyword_moves: &[Move], rc: &RC) -> bool {
+        Board::is_connected_for_keyword(grid, keyword_moves, rc)
+    }
+
+    /// Handles the player blackening a cell while executing this keyword. Returns the progress after the move, or why
+    /// the move is illegal, in which case the board must be left unchanged.
+    fn execute_blacken(
+        &self,
+        ctx: &mut ExecutionContext,
+        progress: &ExecutionProgress,
+        rc: &RC,
+    ) -> Result<ExecutionProgress, MoveError>;
+
+    /// Handles the player changing the letter of a cell while executing this keyword. By default, this is only allowed
+    /// for cells that were ever a wildcard, and it doesn't count towards executing the keyword.
+    fn execute_change_letter(
+        &self,
+        ctx: &mut ExecutionContext,
+        progress: &ExecutionProgress,
+        rc: &RC,
+        letter: char,
+    ) -> Result<ExecutionProgress, MoveError> {
+        ctx.change_wildcard_letter(rc, letter)?;
+        Ok(progress.clone())
+    }
+
+    /// Returns whether the player is done executing this keyword and can go on to gather the next one.
+    fn is_complete(&self, _grid: &Grid<BoardCell>, progress: &ExecutionProgress) -> bool {
+        *progress == ExecutionProgress::Finished
+    }
+
+    /// Returns whether the order that cells are blackened in while executing this keyword can change the outcome. If
+    /// not, the solver only tries them in reading order.
+    fn blacken_order_matters(&self) -> bool {
+        true
+    }
+}
+
+/// What a keyword can see and change while the player is executing it.
+pub struct ExecutionContext<'a> {
+    sim: &'a mut Simulation,
+}
+
+impl<'a> ExecutionContext<'a> {
+    /// Creates a context for a keyword to execute against the given simulation.
+    pub(crate) fn new(sim: &'a mut Simulation) -> ExecutionContext<'a> {
+        ExecutionContext { sim }
+    }
+
+    /// Gets the board as it is before the move being executed.
+    pub fn grid(&self) -> &Grid<BoardCell> {
+        &self.sim.grid
+    }
+
+    /// Blackens a cell.
+    pub fn blacken_cell(&mut self, rc: &RC) {
+        self.sim.blacken_cell(rc);
+    }
+
+    /// Attempts to change the letter in a cell. Returns whether it was permitted.
+    pub fn try_change_cell_letter(&mut self, rc: &RC, letter: char) -> bool {
+        self.sim.try_change_cell_letter(rc, letter)
+    }
+
+    /// Changes the letter in a cell that was ever a wildcard, which the player is permitted to do at any time other
+    /// than when a keyword says otherwise.
+    pub fn change_wildcard_letter(&mut self, rc: &RC, letter: char) -> Result<(), MoveError> {
+        self.sim.change_wildcard_letter(rc, letter)
+    }
+}
+
+/// The keywords that are recognized when checking a solution.
+pub struct KeywordRegistry {
+    keywords: Vec<Box<dyn Keyword>>,
+}
+
+impl KeywordRegistry {
+    /// Creates a registry with no keywords in it.
+    pub fn new() -> KeywordRegistry {
+        KeywordRegistry { keywords: vec![] }
+    }
+
+    /// Creates a registry with the keywords from the game: LOK, TLAK, TA, BE, and LOLO.
+    pub fn standard() -> KeywordRegistry {
+        KeywordRegistry {
+            keywords: vec![
+                Box::new(Lok),
+                Box::new(Tlak),
+                Box::new(Ta),
+                Box::new(Be),
+                Box::new(Lolo),
+            ],
+        }
+    }
+
+    /// Adds a keyword to the registry. Returns its ID, or an error if a keyword with the same letters was already
+    /// registered.
+    pub fn register(&mut self, keyword: Box<dyn Keyword>) -> Result<KeywordId, String> {
+        if self.find(keyword.letters()).is_some() {
+            return Err(format!(
+                "The keyword {} is already registered!",
+                keyword.letters()
+            ));
+        }
+
+        self.keywords.push(keyword);
+        Ok(self.keywords.len() - 1)
+    }
+
+    /// Gets a keyword by its ID.
+    pub fn get(&self, id: KeywordId) -> &dyn Keyword {
+        self.keywords[id].as_ref()
+    }
+
+    /// Finds the keyword spelled exactly by `letters`, if any.
+    pub fn find(&self, letters: &str) -> Option<KeywordId> {
+        self.keywords
+            .iter()
+            .position(|keyword| keyword.letters() == letters)
+    }
+
+    /// Returns whether any keyword starts with `prefix`, i.e. whether gathering could still end up with a keyword.
+    pub fn could_start_with(&self, prefix: &str) -> bool {
+        self.keywords
+            .iter()
+            .any(|keyword| keyword.letters().starts_with(prefix))
+    }
+
+    /// Returns whether `rc` can be gathered next for any keyword starting with `prefix`, after the cells targeted by
+    /// `keyword_moves`.
+    pub fn can_gather(
+        &self,
+        prefix: &str,
+        grid: &Grid<BoardCell>,
+        keyword_moves: &[Move],
+        rc: &RC,
+    ) -> bool {
+        self.keywords
+            .iter()
+            .filter(|keyword| keyword.letters().starts_with(prefix))
+            .any(|keyword| keyword.can_gather(grid, keyword_moves, rc))
+    }
+
+    /// Iterates over the keywords, in the order they were registered.
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Keyword> + '_ {
+        self.keywords.iter().map(|keyword| keyword.as_ref())
+    }
+
+    /// Iterates over every letter used in any keyword, possibly with repeats.
+    pub fn letters(&self) -> impl Iterator<Item = char> + '_ {
+        self.keywords
+            .iter()
+            .flat_map(|keyword| keyword.letters().chars())
+    }
+}
+
+impl Default for KeywordRegistry {
+    fn default() -> KeywordRegistry {
+        KeywordRegistry::new()
+    }
+}
+
+/// The LOK keyword expects the player to blacken one cell anywhere in the board.
+struct Lok;
+
+impl Keyword for Lok {
+    fn letters(&self) -> &'static str {
+        "LOK"
+    }
+
+    fn execute_blacken(
+        &self,
+        ctx: &mut ExecutionContext,
+        _progress: &ExecutionProgress,
+        rc: &RC,
+    ) -> Result<ExecutionProgress, MoveError> {
+        ctx.blacken_cell(rc);
+        Ok(ExecutionProgress::Finished)
+    }
+}
+
+/// The TLAK keyword expects the player to blacken two adjacent cells anywhere on the board. Adjacency is determined by
+/// a true result from `is_adjacent`--see that function for more about what counts as adjacent.
+struct Tlak;
+
+impl Keyword for Tlak {
+    fn letters(&self) -> &'static str {
+        "TLAK"
+    }
+
+    fn execute_blacken(
+        &self,
+        ctx: &mut ExecutionContext,
+        progress: &ExecutionProgress,
+        rc: &RC,
+    ) -> Result<ExecutionProgress, MoveError> {
+        // If this is the second cell, make sure it is adjacent to the first cell.
+        if let ExecutionProgress::ChoseCell(last_exec_rc) = progress {
+            if !Board::is_adjacent(ctx.grid(), last_exec_rc, rc) {
+                trace!(
+                    "{:?} not adjacent to {:?} for TLAK blacken",
+                    last_exec_rc,
+                    rc
+                );
+
+                return Err(ME::TLAKNotAdjacent);
+            }
+
+            ctx.blacken_cell(rc);
+            Ok(ExecutionProgress::Finished)
+        } else {
+            ctx.blacken_cell(rc);
+            Ok(ExecutionProgress::ChoseCell(rc.clone()))
+        }
+    }
+
+    fn blacken_order_matters(&self) -> bool {
+        false
+    }
+}
+
+/// The TA keyword expects the player to blacken all cells on the board with a specified letter. The player specifies
+/// which letter they're targeting by the first cell they choose during the execution phase. Blank cells are also
+/// permitted. Thereafter, the player is expected to target all cells that match the letter.
+struct Ta;
+
+impl Keyword for Ta {
+    fn letters(&self) -> &'static str {
+        "TA"
+    }
+
+    fn execute_blacken(
+        &self,
+        ctx: &mut ExecutionContext,
+        progress: &ExecutionProgress,
+        rc: &RC,
+    ) -> Result<ExecutionProgress, MoveError> {
+        let target = ctx.grid()[rc];
+        if let Some(letter) = target.get_letter_or_blank() {
+            // If the user has chosen a letter from a previous move during this execution, make sure the new letter
+            // being chosen matches it.
+            if let ExecutionProgress::ChoseLetter(chosen_letter) = progress {
+                if letter != *chosen_letter {
+                    trace!(
+                        "Letter {} does not match TA chosen letter {}",
+                        letter,
+                        chosen_letter
+                    );
+
+                    return Err(ME::TALetterMismatch);
+                }
+            } else {
+                trace!("TA choosing letter {}", letter);
+            }
+
+            ctx.blacken_cell(rc);
+            Ok(ExecutionProgress::ChoseLetter(letter))
+        } else {
+            trace!("Not a letter: {}", target.get_raw());
+            Err(ME::TAInvalidLetter)
+        }
+    }
+
+    /// If there are any more of the chosen letter on the board, then the player still has to blacken those. Otherwise,
+    /// the TA is done.
+    fn is_complete(&self, grid: &BoardGrid, progress: &ExecutionProgress) -> bool {
+        let letter = match progress {
+            ExecutionProgress::ChoseLetter(letter) => *letter,
+            _ => return false,
+        };
+
+        for (rc, cell) in grid.enumerate_row_col() {
+            if cell.is_blackened() {
+                continue;
+            }
+
+            if cell.get_letter_or_blank() == Some(letter) {
+                trace!("{:?} is still {}", rc, letter);
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn blacken_order_matters(&self) -> bool {
+        false
+    }
+}
+
+/// The BE keyword expects the player to fill in one blank cell with a letter of their choice.
+struct Be;
+
+impl Keyword for Be {
+    fn letters(&self) -> &'static str {
+        "BE"
+    }
+
+    fn execute_blacken(
+        &self,
+        _ctx: &mut ExecutionContext,
+        _progress: &ExecutionProgress,
+        _rc: &RC,
+    ) -> Result<ExecutionProgress, MoveError> {
+        trace!("Cannot blacken while executing BE");
+        Err(ME::BECannotBlacken)
+    }
+
+    fn execute_change_letter(
+        &self,
+        ctx: &mut ExecutionContext,
+        _progress: &ExecutionProgress,
+        rc: &RC,
+        letter: char,
+    ) -> Result<ExecutionProgress, MoveError> {
+        // BE requires the target cell to be blank.
+        let target = ctx.grid()[rc];
+        if !target.is_blank() {
+            trace!(
+                "Not allowed to change letter in non-blank cell: {:?}",
+                target.get_letter()
+            );
+            return Err(ME::BECannotChangeNonBlankCell);
+        }
+
+        if letter == BLANK_LETTER || !ctx.try_change_cell_letter(rc, letter) {
+            trace!("Not allowed to change letter to '{}'", letter);
+            return Err(ME::BECannotChangeToThisLetter);
+        }
+
+        Ok(ExecutionProgress::Finished)
+    }
+}
+
+/// The LOLO keyword expects the player to choose a cell and then blacken all cells in a diagonal line extending
+/// down-left and up-right from there. The order of blackening doesn't matter, but all cells along that diagonal must
+/// be blackened.
+struct Lolo;
+
+impl Keyword for Lolo {
+    fn letters(&self) -> &'static str {
+        "LOLO"
+    }
+
+    fn execute_blacken(
+        &self,
+        ctx: &mut ExecutionContext,
+        progress: &ExecutionProgress,
+        rc: &RC,
+    ) -> Result<ExecutionProgress, MoveError> {
+        if let ExecutionProgress::ChoseCell(anchor_rc) = progress {
+            if !Board::is_on_lolo_path(ctx.grid(), anchor_rc, rc) {
+                trace!("{:?} is not on LOLO path", rc);
+                return Err(ME::LOLONotOnPath);
+            }
+
+            ctx.blacken_cell(rc);
+            Ok(progress.clone())
+        } else {
+            ctx.blacken_cell(rc);
+            Ok(ExecutionProgress::ChoseCell(rc.clone()))
+        }
+    }
+
+    /// All cells on the diagonal path must be done before the execution can stop.
+    fn is_complete(&self, grid: &BoardGrid, progress: &ExecutionProgress) -> bool {
+        let anchor_rc = match progress {
+            ExecutionProgress::ChoseCell(anchor_rc) => anchor_rc,
+            _ => return false,
+        };
+
+        for (rc, cell) in grid.enumerate_row_col() {
+            if !Board::is_on_lolo_path(grid, anchor_rc, &rc) {
+                continue;
+            }
+
+            if !cell.is_done() {
+                trace!(
+                    "{:?} on LOLO path including {:?} is still not done",
+                    rc,
+                    anchor_rc
+                );
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn blacken_order_matters(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SolutionResult, SR};
+
+    /// A house-rule keyword that expects the player to blacken a single cell that has an L in it.
+    struct Ko;
+
+    impl Keyword for Ko {
+        fn letters(&self) -> &'static str {
+            "KO"
+        }
+
+        fn execute_blacken(
+            &self,
+            ctx: &mut ExecutionContext,
+            _progress: &ExecutionProgress,
+            rc: &RC,
+        ) -> Result<ExecutionProgress, MoveError> {
+            if ctx.grid()[rc].get_letter() != Some('L') {
+                return Err(ME::CustomKeyword("KO needs an L".to_string()));
+            }
+
+            ctx.blacken_cell(rc);
+            Ok(ExecutionProgress::Finished)
+        }
+    }
+
+    fn registry_with_ko() -> KeywordRegistry {
+        let mut keywords = KeywordRegistry::standard();
+        keywords.register(Box::new(Ko)).unwrap();
+        keywords
+    }
+
+    #[test]
+    fn standard_registry() {
+        let keywords = KeywordRegistry::standard();
+        assert!(keywords.find("LOK").is_some());
+        assert!(keywords.find("LO").is_none());
+        assert!(keywords.could_start_with("TL"));
+        assert!(!keywords.could_start_with("KO"));
+    }
+
+    #[test]
+    fn register_twice() {
+        let mut keywords = KeywordRegistry::standard();
+        assert!(keywords.register(Box::new(Lok)).is_err());
+        assert!(keywords.register(Box::new(Ko)).is_ok());
+        assert!(keywords.register(Box::new(Ko)).is_err());
+    }
+
+    #[test]
+    fn custom_keyword() {
+        let mut board = Board::with_keywords("KOL", registry_with_ko()).unwrap();
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        assert_eq!(board.check_solution(), SR::NotIdle);
+        board.blacken(0, 2).unwrap();
+        assert_eq!(board.check_solution(), SolutionResult::Correct);
+    }
+
+    #[test]
+    fn custom_keyword_rejects_move() {
+        let mut board = Board::with_keywords("KOLX", registry_with_ko()).unwrap();
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 3).unwrap();
+        assert_eq!(
+            board.check_solution(),
+            SR::ErrorOnMove(2, ME::CustomKeyword("KO needs an L".to_string()))
+        );
+    }
+
+    #[test]
+    fn custom_keyword_unknown_to_standard_board() {
+        let mut board = Board::new("KOL").unwrap();
+        board.blacken(0, 0).unwrap();
+        assert_eq!(
+            board.check_solution(),
+            SR::ErrorOnMove(0, ME::UnknownKeyword)
+        );
+    }
+
+    #[test]
+    fn solve_with_custom_keyword() {
+        let board = Board::with_keywords("KOL", registry_with_ko()).unwrap();
+        assert!(board.is_solvable());
+        assert!(!Board::new("KOL").unwrap().is_solvable());
+    }
+}
@@ -1,24 +1,57 @@
-use wasm_bindgen::prelude::*;
-
-extern crate web_sys;
+use std::fmt;
+use std::rc::Rc;
+use std::str::FromStr;
 
-mod grid;
-mod utils;
+use wasm_bindgen::prelude::*;
 
-use crate::grid::{Grid, RC};
+// Macros to provide `println!(..)`-style syntax for logging at each level. See the `logging` module for where the
+// messages go. Nothing is formatted unless the level is enabled.
+macro_rules! info {
+    ( $( $t:tt )* ) => {
+        if $crate::logging::is_enabled($crate::logging::LogLevel::Info) {
+            $crate::logging::write($crate::logging::LogLevel::Info, format_args!( $( $t )* ));
+        }
+    }
+}
 
-// A macro to provide `println!(..)`-style syntax for `console.log` logging. On non-wasm platforms, thunks to println!.
-macro_rules! log {
+macro_rules! trace {
     ( $( $t:tt )* ) => {
-        if cfg!(target_family = "wasm") {
-            web_sys::console::log_1(&format!( $( $t )* ).into());
-        } else {
-            println!( $( $t )* );
+        if $crate::logging::is_enabled($crate::logging::LogLevel::Trace) {
+            $crate::logging::write($crate::logging::LogLevel::Trace, format_args!( $( $t )* ));
         }
     }
 }
 
-const KNOWN_KEYWORDS: [&'static str; 5] = ["LOK", "TLAK", "TA", "BE", "LOLO"];
+pub mod canonical;
+pub mod difficulty;
+pub mod generator;
+pub mod grid;
+mod history;
+pub mod keyword;
+pub mod logging;
+pub mod pack;
+pub mod render;
+pub mod replay;
+#[cfg(feature = "serde")]
+mod serialization;
+pub mod share;
+mod solver;
+pub mod storage;
+pub mod svg;
+pub mod trace;
+mod utils;
+
+use crate::grid::{Direction, Grid, RC};
+use crate::history::{HistoryTree, NodeId};
+use crate::keyword::{ExecutionContext, KeywordId, KeywordRegistry};
+use crate::storage::Autosave;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+pub use crate::keyword::ExecutionProgress;
+pub use crate::solver::{Hint, SolutionCount, Solvability};
+
 const GAP_LETTER: char = '-';
 const BLANK_LETTER: char = '_';
 const CONDUCTOR_LETTER: char = 'X';
@@ -26,6 +59,7 @@ const WILDCARD_LETTER: char = '?';
 
 #[wasm_bindgen]
 #[derive(Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct BoardCell {
     letter: Option<char>,
     is_blackened: bool,
@@ -66,11 +100,16 @@ impl BoardCell {
 
 impl BoardCell {
     /// Constructs a new cell with the given letter. The cell may be end up having a special function like being a gap,
-    /// conductor, etc., based on what is provided in `letter`.
-    fn raw(letter: char) -> BoardCell {
-        assert!(letter.is_ascii());
+    /// conductor, etc., based on what is provided in `letter`. Fails if the letter isn't ASCII.
+    fn raw(letter: char) -> Result<BoardCell, String> {
+        if !letter.is_ascii() {
+            return Err(format!(
+                "The character '{}' isn't allowed, because only ASCII characters are allowed!",
+                letter
+            ));
+        }
 
-        BoardCell {
+        Ok(BoardCell {
             letter: match letter {
                 GAP_LETTER => None,
                 _ => Some(letter.to_ascii_uppercase()),
@@ -79,20 +118,12 @@ impl BoardCell {
             is_blackened: false,
             is_marked_for_path: false,
             mark_count: 0,
-        }
-    }
-
-    /// Creates a blank cell, not a gap.
-    fn blank() -> BoardCell {
-        BoardCell::raw(BLANK_LETTER)
+        })
     }
 
     /// Returns whether this is a blank (not gap) cell.
     fn is_blank(&self) -> bool {
-        match self.letter {
-            Some(BLANK_LETTER) => true,
-            _ => false,
-        }
+        matches!(self.letter, Some(BLANK_LETTER))
     }
 
     /// Returns if this cell is considered complete for purposes of checking if the whole puzzle is solved.
@@ -131,10 +162,7 @@ impl BoardCell {
 
     /// Returns the letter in this cell, allowing returning the blank character too.
     fn get_letter_or_blank(&self) -> Option<char> {
-        match self.letter {
-            None => None,
-            Some(ch) => Some(ch),
-        }
+        self.letter
     }
 
     /// Returns the letter in this cell, and assumes it is not a gap.
@@ -171,10 +199,16 @@ impl BoardCell {
     }
 }
 
-#[derive(Clone, Debug)]
-enum Move {
+/// A single move made by the player.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Move {
+    /// Blackens the cell at the given location.
     Blacken(RC),
+
+    /// Marks the cell at the given location as part of a path.
     MarkPath(RC),
+
+    /// Changes the letter in the cell at the given location.
     ChangeLetter(RC, char),
 }
 
@@ -189,38 +223,67 @@ impl Move {
     }
 }
 
-#[derive(Clone, Debug)]
+/// Formats a move as text, e.g. `B 0 1` to blacken row 0, col 1, `M 2 3` to mark row 2, col 3 for a path, or `C 0 3 T`
+/// to change the letter in row 0, col 3 to T.
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Move::Blacken(RC(row, col)) => write!(f, "B {} {}", row, col),
+            Move::MarkPath(RC(row, col)) => write!(f, "M {} {}", row, col),
+            Move::ChangeLetter(RC(row, col), letter) => write!(f, "C {} {} {}", row, col, letter),
+        }
+    }
+}
+
+/// Parses a move in the format produced by its `Display` implementation.
+impl FromStr for Move {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Move, String> {
+        let parts: Vec<&str> = s.split_whitespace().collect();
+
+        let parse_rc = |row: &str, col: &str| -> Result<RC, String> {
+            let row = row
+                .parse()
+                .map_err(|_| format!("Invalid row \"{}\"", row))?;
+            let col = col
+                .parse()
+                .map_err(|_| format!("Invalid col \"{}\"", col))?;
+            Ok(RC(row, col))
+        };
+
+        match parts[..] {
+            ["B", row, col] => Ok(Move::Blacken(parse_rc(row, col)?)),
+            ["M", row, col] => Ok(Move::MarkPath(parse_rc(row, col)?)),
+            ["C", row, col, letter] => {
+                let mut chars = letter.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(ch), None) if ch.is_ascii_graphic() && ch != GAP_LETTER => {
+                        Ok(Move::ChangeLetter(parse_rc(row, col)?, ch))
+                    }
+                    _ => Err(format!("Cannot change a letter to \"{}\"", letter)),
+                }
+            }
+            _ => Err(format!("Unrecognized move \"{}\"", s)),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
 enum BoardState {
-    // In this state, the player is choosing the cells to be used in a keyword. There are a certain number of recognized
-    // keywords, given in `KNOWN_KEYWORDS`. The letters of a keyword must be connected such that the result of
+    // In this state, the player is choosing the cells to be used in a keyword. The recognized keywords are the ones in
+    // the simulation's `KeywordRegistry`. The letters of a keyword must be connected such that the result of
     // `is_connected_for_keyword` is true between them--see that function for more notes on how keywords can be
     // connected.
     //
     // Once the entire keyword is found, the cells are blackened out and then the player is expected to execute the
-    // keyword. See the below states for the expectations of each individual keyword.
+    // keyword. See the `Keyword` implementations for the expectations of each individual keyword.
     //
     // Once the keyword is executed, the simulation returns to the idle state, which is gathering the next keyword.
     GatheringKeyword(String, Vec<Move>),
 
-    // The LOK keyword expects the player to blacken one cell anywhere in the board.
-    ExecutingLOK,
-
-    // The TLAK keyword expects the player to blacken two adjacent cells anywhere on the board. Adjacency is determined
-    // by a true result from `is_adjacent`--see that function for more about what counts as adjacent.
-    ExecutingTLAK(Option<RC>),
-
-    // The TA keyword expects the player to blacken all cells on the board with a specified letter. The player specifies
-    // which letter they're targeting by the first cell they choose during the execution phase. Blank cells are also
-    // permitted. Thereafter, the player is expected to target all cells that match the letter.
-    ExecutingTA(Option<char>),
-
-    // The BE keyword expects the player to fill in one blank cell with a letter of their choice.
-    ExecutingBE,
-
-    // The LOLO keyword expects the player to choose a cell and then blacken all cells in a diagonal line extending
-    // down-left and up-right from there. The order of blackening doesn't matter, but all cells along that diagonal must
-    // be blackened.
-    ExecutingLOLO(Option<RC>),
+    // The player is executing the given keyword from the registry and has gotten this far.
+    ExecutingKeyword(KeywordId, ExecutionProgress),
 }
 
 impl BoardState {
@@ -230,13 +293,38 @@ impl BoardState {
     }
 }
 
+/// A change that a move made to a single cell, with the values before and after.
+#[derive(Clone)]
+struct CellChange {
+    rc: RC,
+    before: BoardCell,
+    after: BoardCell,
+}
+
+/// What is needed to undo a move that was applied to the simulation.
+struct SimulationUndo {
+    state: BoardState,
+    changes: Vec<CellChange>,
+}
+
+/// A move that the player made, along with only the cells it changed, for undo.
 struct BoardStep {
     mv: Move,
-    grid: BoardGrid,
+
+    // The change to the board as displayed to the player.
+    change: CellChange,
+
+    // How to undo this move in the simulation. None if the simulation had already stopped at an illegal move.
+    sim_undo: Option<SimulationUndo>,
+
+    // Where this move is in the history tree.
+    history_node: NodeId,
 }
 
-#[derive(PartialEq, Debug)]
-enum MoveError {
+/// Why a move was illegal according to the rules.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MoveError {
     AlreadyBlackened,
     BlackenNotConnectedForKeyword,
     PathNotConnectedForKeyword,
@@ -252,10 +340,15 @@ enum MoveError {
     CellCannotChangeLetterInThisState,
     BECannotChangeNonBlankCell,
     BECannotChangeToThisLetter,
+
+    /// A keyword from outside the game, registered in a `KeywordRegistry`, didn't allow the move, for this reason.
+    CustomKeyword(String),
 }
 
+/// The result of checking the moves the player has made as a solution to the puzzle.
 #[derive(PartialEq, Debug)]
-enum SolutionResult {
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SolutionResult {
     /// The solution is correct.
     Correct,
 
@@ -272,191 +365,547 @@ enum SolutionResult {
     ErrorOnMove(usize, MoveError),
 }
 
+impl SolutionResult {
+    /// Gets the name of this result, without any of its data.
+    pub fn name(&self) -> &'static str {
+        match self {
+            SR::Correct => "Correct",
+            SR::Incomplete => "Incomplete",
+            SR::NotIdle => "NotIdle",
+            SR::PartialKeyword => "PartialKeyword",
+            SR::ErrorOnMove(_, _) => "ErrorOnMove",
+        }
+    }
+}
+
+/// A detailed explanation of the result of checking a solution. This reveals a lot about how the rules work, so it is
+/// meant for puzzle authors and should only be shown when explicitly asked for.
+#[wasm_bindgen]
+pub struct CheckDetails {
+    result: &'static str,
+    error: Option<String>,
+    move_index: Option<usize>,
+    rc: Option<RC>,
+}
+
+#[wasm_bindgen]
+impl CheckDetails {
+    /// Gets the kind of result, e.g. "Correct" or "ErrorOnMove".
+    pub fn get_result(&self) -> String {
+        self.result.to_string()
+    }
+
+    /// Gets why the move was illegal, e.g. "AlreadyBlackened", if the result is "ErrorOnMove".
+    pub fn get_error(&self) -> Option<String> {
+        self.error.clone()
+    }
+
+    /// Gets the 0-based index of the illegal move, if the result is "ErrorOnMove".
+    pub fn get_move_index(&self) -> Option<usize> {
+        self.move_index
+    }
+
+    /// Gets the row of the cell involved in the result, if any. For an illegal move, this is the cell it targeted. For
+    /// an incomplete board, it is the first cell that still needs to be blackened. For a partially gathered keyword, it
+    /// is the last cell gathered.
+    pub fn get_row(&self) -> Option<usize> {
+        self.rc.as_ref().map(|rc| rc.0)
+    }
+
+    /// Gets the column of the cell involved in the result, if any. See `get_row`.
+    pub fn get_col(&self) -> Option<usize> {
+        self.rc.as_ref().map(|rc| rc.1)
+    }
+}
+
 // Shorthand
 type SR = SolutionResult;
 type ME = MoveError;
 
 #[wasm_bindgen]
 pub struct Board {
+    // The board as the puzzle was given, before any moves.
     grid: BoardGrid,
+
+    // The board as displayed to the player after all of their moves.
+    latest: BoardGrid,
+
+    // The moves that are currently applied, in order. These are one path through `history`.
     moves: Vec<BoardStep>,
+
+    // Every move the player has made, including ones that were undone, so they can be redone.
+    history: HistoryTree<Move>,
+
+    // The simulation after all of the moves up to the first illegal one, and that illegal move, if any.
+    sim: Simulation,
+    first_error: Option<(usize, MoveError)>,
+
+    // Whether moves that are illegal according to the rules should be rejected instead of tracked.
+    is_strict: bool,
+
+    // The keywords recognized when checking the solution.
+    keywords: Rc<KeywordRegistry>,
+
+    // Where the moves are saved after every change, if anywhere.
+    autosave: Option<Autosave>,
 }
 
 #[wasm_bindgen]
 impl Board {
     /// Constructs a new board, given player input.
     pub fn new(contents: &str) -> Result<Board, String> {
-        log!("puzzle:\n{}", contents);
-
-        // First determine the size of the board. It is inferred from the number of lines and the length of each line.
-        let mut rows = 0;
-        let mut cols = 0;
-        for line in contents.lines() {
-            if cols == 0 {
-                cols = line.len();
+        Board::with_keywords(contents, KeywordRegistry::standard())
+    }
+
+    /// Gets the number of columns in the board.
+    pub fn width(&self) -> u32 {
+        self.grid.width() as u32
+    }
+
+    /// Gets the number of rows in the board.
+    pub fn height(&self) -> u32 {
+        self.grid.height() as u32
+    }
+
+    /// Gets the specified location on the board. The upper-left corner is `RC(0, 0)`.
+    pub fn get(&self, row: usize, col: usize) -> Result<BoardCell, String> {
+        let rc = self.check_bounds(row, col)?;
+        Ok(self.get_latest()[&rc])
+    }
+
+    /// Marks the specified cell as blackened and tracks this move in the solution. Returns whether the move was
+    /// tracked, which is always the case unless strict mode is on.
+    pub fn blacken(&mut self, row: usize, col: usize) -> Result<bool, String> {
+        let target_rc = self.check_bounds(row, col)?;
+        Ok(self.make_move(&Move::Blacken(target_rc)))
+    }
+
+    /// Marks the specified cell as part of a path and tracks this move in the solution. Returns whether the move was
+    /// tracked, which is always the case unless strict mode is on.
+    pub fn mark_path(&mut self, row: usize, col: usize) -> Result<bool, String> {
+        let target_rc = self.check_bounds(row, col)?;
+        Ok(self.make_move(&Move::MarkPath(target_rc)))
+    }
+
+    /// Changes the letter in a cell and tracks this move in the solution. Returns whether the move was tracked. Changing
+    /// a cell to a gap is never tracked, and in strict mode, neither are illegal moves. Only printable ASCII characters
+    /// are supported.
+    pub fn change_letter(&mut self, row: usize, col: usize, letter: char) -> Result<bool, String> {
+        let target_rc = self.check_bounds(row, col)?;
+        if !letter.is_ascii_graphic() {
+            return Err(format!(
+                "Cannot change a letter to '{}', because only printable ASCII characters are allowed!",
+                letter.escape_default()
+            ));
+        }
+
+        Ok(self.make_move(&Move::ChangeLetter(target_rc, letter)))
+    }
+
+    /// Removes the latest move from the solution.
+    pub fn undo(&mut self) {
+        if let Some(step) = self.moves.pop() {
+            self.latest[&step.change.rc] = step.change.before;
+
+            if let Some(sim_undo) = step.sim_undo {
+                self.sim.undo(sim_undo);
             }
 
-            if line.len() != cols {
-                return Err(format!(
-                    "Row {} had {} cols, but needed to have {} cols to match the rows above it!",
-                    rows,
-                    line.len(),
-                    cols
-                ));
+            // If this was the first illegal move, the simulation can continue from here again.
+            if matches!(self.first_error, Some((mv_num, _)) if mv_num == self.moves.len()) {
+                self.first_error = None;
             }
 
-            rows += 1;
+            self.save_progress();
         }
+    }
 
-        let mut board = Board {
-            grid: Grid::new(cols, rows, &BoardCell::blank()),
-            moves: vec![],
-        };
+    /// Gets the number of moves currently applied.
+    pub fn move_count(&self) -> usize {
+        self.moves.len()
+    }
 
-        // Fill in the board.
-        let mut row = 0;
-        for line in contents.lines() {
-            let mut col = 0;
-            for ch in line.chars() {
-                board.grid[&RC(row, col)] = BoardCell::raw(ch);
-                col += 1;
+    /// Re-applies the most recently undone move. Returns whether there was a move to redo and it was tracked.
+    pub fn redo(&mut self) -> bool {
+        match self.history.active_child(self.current_history_node()) {
+            Some(node) => {
+                let mv = self.history.get(node).unwrap().clone();
+                self.make_move(&mv)
             }
-
-            row += 1;
+            None => false,
         }
+    }
 
-        Ok(board)
+    /// Returns whether there is an undone move that can be redone.
+    pub fn can_redo(&self) -> bool {
+        self.history
+            .active_child(self.current_history_node())
+            .is_some()
     }
 
-    /// Gets the number of columns in the board.
-    pub fn width(&self) -> u32 {
-        self.grid.width() as u32
+    /// Gets the number of different moves that have been made after the given number of moves. More than one means
+    /// that the player went back and tried something else at that point.
+    pub fn branch_count(&self, step: usize) -> usize {
+        self.history_node_at(step)
+            .map_or(0, |node| self.history.children(node).len())
     }
 
-    /// Gets the number of rows in the board.
-    pub fn height(&self) -> u32 {
-        self.grid.height() as u32
+    /// Gets which of the branches after the given number of moves was most recently followed, if any.
+    pub fn active_branch(&self, step: usize) -> Option<usize> {
+        self.history_node_at(step)
+            .and_then(|node| self.history.active_branch(node))
     }
 
-    /// Gets the specified location on the board. The upper-left corner is `RC(0, 0)`.
-    pub fn get(&self, row: usize, col: usize) -> BoardCell {
-        self.get_latest()[&RC(row, col)].clone()
+    /// Undoes back to the given number of moves, then follows a different branch of moves from there, as far as it
+    /// was explored. Returns false if there is no such branch.
+    pub fn switch_branch(&mut self, step: usize, branch: usize) -> bool {
+        match self.history_node_at(step) {
+            Some(node) if self.history.set_active_branch(node, branch) => {
+                self.without_saving_each_step(|board| {
+                    while board.moves.len() > step {
+                        board.undo();
+                    }
+
+                    while board.redo() {}
+                });
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Turns strict mode on or off. In strict mode, moves that break the rules are rejected as soon as they are made,
+    /// rather than only being found when checking the solution.
+    pub fn set_strict(&mut self, is_strict: bool) {
+        self.is_strict = is_strict;
     }
 
-    /// Marks the specified cell as blackened and tracks this move in the solution.
-    pub fn blacken(&mut self, row: usize, col: usize) {
-        assert!(row < self.grid.height());
-        assert!(col < self.grid.width());
+    pub fn check(&self) -> bool {
+        self.check_solution() == SolutionResult::Correct
+    }
 
-        // Make a copy of the entire board and store that with the move, for easy undo.
-        let target_rc = RC(row, col);
-        let mut new_grid = self.get_latest().clone();
-        new_grid[&target_rc].blacken();
+    /// Checks the solution like `check`, but explains why it is wrong.
+    pub fn check_detailed(&self) -> CheckDetails {
+        let result = self.check_solution();
+
+        let rc = match &result {
+            SR::ErrorOnMove(mv_num, _) => Some(self.moves[*mv_num].mv.get_rc().clone()),
+            SR::Incomplete => self
+                .sim
+                .grid
+                .enumerate_row_col()
+                .find(|(_, cell)| !cell.is_done())
+                .map(|(rc, _)| rc),
+            SR::PartialKeyword => match &self.sim.state {
+                BoardState::GatheringKeyword(_, keyword_moves) => {
+                    keyword_moves.last().map(|mv| mv.get_rc().clone())
+                }
+                _ => None,
+            },
+            SR::Correct | SR::NotIdle => None,
+        };
 
-        self.moves.push(BoardStep {
-            mv: Move::Blacken(target_rc.clone()),
-            grid: new_grid,
-        });
+        let (error, move_index) = match &result {
+            SR::ErrorOnMove(mv_num, err) => (Some(format!("{:?}", err)), Some(*mv_num)),
+            _ => (None, None),
+        };
+
+        CheckDetails {
+            result: result.name(),
+            error,
+            move_index,
+            rc,
+        }
     }
 
-    /// Marks the specified cell as part of a path and tracks this move in the solution.
-    pub fn mark_path(&mut self, row: usize, col: usize) {
-        assert!(row < self.grid.height());
-        assert!(col < self.grid.width());
+    /// Exports the moves that are currently applied as text, with one move per line. See `import_moves`.
+    pub fn export_moves(&self) -> String {
+        self.moves()
+            .map(|mv| mv.to_string())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
 
-        // Make a copy of the entire board and store that with the move, for easy undo.
-        let target_rc = RC(row, col);
-        let mut new_grid = self.get_latest().clone();
-        new_grid[&target_rc].mark_path();
+    /// Replaces the moves that are currently applied with moves exported by `export_moves`. Blank lines and lines
+    /// starting with `#` are ignored. If any move can't be parsed or is out of bounds, no moves are changed. The moves
+    /// are tracked even if they break the rules, regardless of strict mode.
+    pub fn import_moves(&mut self, text: &str) -> Result<(), String> {
+        let mut moves = vec![];
+        for (line_num, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
 
-        self.moves.push(BoardStep {
-            mv: Move::MarkPath(target_rc.clone()),
-            grid: new_grid,
+            let mv: Move = line
+                .parse()
+                .map_err(|err| format!("Line {}: {}", line_num + 1, err))?;
+
+            let RC(row, col) = mv.get_rc();
+            self.check_bounds(*row, *col)
+                .map_err(|err| format!("Line {}: {}", line_num + 1, err))?;
+
+            moves.push(mv);
+        }
+
+        self.without_saving_each_step(|board| {
+            while !board.moves.is_empty() {
+                board.undo();
+            }
+
+            let is_strict = board.is_strict;
+            board.is_strict = false;
+            for mv in moves.iter() {
+                let is_tracked = board.make_move(mv);
+                assert!(is_tracked);
+            }
+            board.is_strict = is_strict;
         });
+
+        Ok(())
     }
+}
 
-    /// Changes the letter in a cell and tracks this move in the solution.
-    pub fn change_letter(&mut self, row: usize, col: usize, letter: char) {
-        assert!(row < self.grid.height());
-        assert!(col < self.grid.width());
+impl Board {
+    /// Constructs a new board like `new`, but recognizing a different set of keywords when checking the solution.
+    pub fn with_keywords(contents: &str, keywords: KeywordRegistry) -> Result<Board, String> {
+        utils::set_panic_hook();
+
+        info!("puzzle:\n{}", contents);
+
+        // The size of the board is inferred from the number of lines and the length of each line. Empty lines above the
+        // first one with anything in it are rows of blanks, as wide as that line. A puzzle with nothing but empty lines
+        // has no width, so it's rejected, like any other grid with rows but no cols.
+        let blank_rows = contents.lines().take_while(|line| line.is_empty()).count();
+        let letters: Grid<char> = if blank_rows == 0 {
+            contents.parse()?
+        } else {
+            let rest = contents.splitn(blank_rows + 1, '\n').last().unwrap_or("");
+            let mut letters: Grid<char> = rest.parse().map_err(|err| {
+                format!(
+                    "In the rows after the {} empty lines at the top: {}",
+                    blank_rows, err
+                )
+            })?;
+
+            if letters.width() == 0 {
+                return Err(format!(
+                    "There are {} rows, but none of them have any cols!",
+                    blank_rows
+                ));
+            }
+
+            for _ in 0..blank_rows {
+                letters.insert_row(0, &BLANK_LETTER);
+            }
+            letters
+        };
 
-        // Make a copy of the entire board and store that with the move, for easy undo.
-        let target_rc = RC(row, col);
-        let mut new_grid = self.get_latest().clone();
-        if !new_grid[&target_rc].try_change_letter(letter) {
-            return;
+        // Only ASCII characters are supported in the puzzle.
+        let mut grid = BoardGrid::new(
+            letters.width(),
+            letters.height(),
+            &BoardCell::raw(GAP_LETTER)?,
+        );
+        for (rc, ch) in letters.enumerate_row_col() {
+            grid[&rc] = BoardCell::raw(*ch).map_err(|_| {
+                format!(
+                    "Row {} col {} has the character '{}', but only ASCII characters are allowed!",
+                    rc.0, rc.1, ch
+                )
+            })?;
         }
 
-        self.moves.push(BoardStep {
-            mv: Move::ChangeLetter(target_rc.clone(), letter),
-            grid: new_grid,
-        });
+        Ok(Board::from_grid(grid, keywords))
     }
 
-    /// Removes the latest move from the solution.
-    pub fn undo(&mut self) {
-        let _ = self.moves.pop();
+    /// Constructs a new board from a grid that was already filled in, with no moves made.
+    fn from_grid(grid: BoardGrid, keywords: KeywordRegistry) -> Board {
+        let keywords = Rc::new(keywords);
+        Board {
+            latest: grid.clone(),
+            sim: Simulation::new(grid.clone(), keywords.clone()),
+            grid,
+            moves: vec![],
+            history: HistoryTree::new(),
+            first_error: None,
+            is_strict: false,
+            keywords,
+            autosave: None,
+        }
     }
 
-    pub fn check(&self) -> bool {
-        self.check_solution() == SolutionResult::Correct
+    /// Creates a simulation of this puzzle from its initial state, before any moves.
+    fn new_simulation(&self) -> Simulation {
+        Simulation::new(self.grid.clone(), self.keywords.clone())
     }
-}
 
-impl Board {
     /// Returns the latest state of the board according to the moves that the player has made.
     fn get_latest(&self) -> &BoardGrid {
-        if let Some(step) = self.moves.last() {
-            &step.grid
-        } else {
-            &self.grid
+        &self.latest
+    }
+
+    /// Returns a grid of cells as puzzle text, with one line per row and the same letters as in the puzzle given to
+    /// `new`.
+    fn puzzle_text(grid: &BoardGrid) -> String {
+        grid.map(|cell| cell.get_letter_or_blank().unwrap_or(GAP_LETTER))
+            .to_string()
+    }
+
+    /// Returns the state of the board as the player saw it after the given number of the moves that are currently
+    /// applied, or None if there haven't been that many.
+    fn get_at_step(&self, step: usize) -> Option<BoardGrid> {
+        if step > self.moves.len() {
+            return None;
+        }
+
+        let mut grid = self.latest.clone();
+        for later_step in self.moves[step..].iter().rev() {
+            grid[&later_step.change.rc] = later_step.change.before;
         }
+
+        Some(grid)
+    }
+
+    /// Returns the first move of each branch after the given number of moves. See `branch_count`.
+    pub fn branches(&self, step: usize) -> Vec<&Move> {
+        self.history_node_at(step).map_or(vec![], |node| {
+            self.history
+                .children(node)
+                .iter()
+                .map(|child| self.history.get(*child).unwrap())
+                .collect()
+        })
+    }
+
+    /// Returns the location as an `RC`, or an error if it is outside of the board.
+    fn check_bounds(&self, row: usize, col: usize) -> Result<RC, String> {
+        let rc = RC(row, col);
+        if !self.grid.contains(&rc) {
+            return Err(format!(
+                "Row {} col {} is outside of the {}x{} board!",
+                row,
+                col,
+                self.grid.height(),
+                self.grid.width()
+            ));
+        }
+
+        Ok(rc)
     }
 
-    /// Returns if two locations are considered adjacent to each other, according to the game's adjacency rules.
-    fn is_adjacent(grid: &BoardGrid, rc1: &RC, rc2: &RC) -> bool {
-        // A cell is not adjacent to itself.
-        if rc1 == rc2 {
-            return false;
+    /// Makes a move that is known to be within the board, as if the player had done it. Returns whether the move was
+    /// tracked.
+    fn make_move(&mut self, mv: &Move) -> bool {
+        let mut new_cell = self.latest[mv.get_rc()];
+        match mv {
+            Move::Blacken(_) => new_cell.blacken(),
+            Move::MarkPath(_) => new_cell.mark_path(),
+            Move::ChangeLetter(_, letter) => {
+                if !new_cell.try_change_letter(*letter) {
+                    return false;
+                }
+            }
         }
 
-        // Must be either vertically or horizontally aligned.
-        if rc1.0 != rc2.0 && rc1.1 != rc2.1 {
-            return false;
+        self.push_step(mv.clone(), new_cell)
+    }
+
+    /// Returns the history node for the latest move that is currently applied.
+    fn current_history_node(&self) -> NodeId {
+        self.moves
+            .last()
+            .map_or(history::ROOT, |step| step.history_node)
+    }
+
+    /// Returns the history node after the given number of currently applied moves, if there have been that many.
+    fn history_node_at(&self, step: usize) -> Option<NodeId> {
+        match step {
+            0 => Some(history::ROOT),
+            _ => self.moves.get(step - 1).map(|step| step.history_node),
         }
+    }
 
-        // Create deltas to walk from one cell to the other. These can each be +1, 0, or -1.
-        let row_walk_inc: isize = rc2.0.cmp(&rc1.0) as i8 as isize;
-        let col_walk_inc: isize = rc2.1.cmp(&rc1.1) as i8 as isize;
-        assert!(row_walk_inc == 0 || col_walk_inc == 0);
-        assert!(row_walk_inc >= -1);
-        assert!(col_walk_inc >= -1);
-        assert!(row_walk_inc <= 1);
-        assert!(col_walk_inc <= 1);
-
-        log!(
-            "Walk from {:?} to {:?}, using direction ({}, {})",
-            rc1,
-            rc2,
-            row_walk_inc,
-            col_walk_inc
-        );
+    /// Returns the moves that the player has made so far, in order.
+    pub fn moves(&self) -> impl Iterator<Item = &Move> {
+        self.moves.iter().map(|step| &step.mv)
+    }
 
-        let mut current_rc = rc1.clone();
-        loop {
-            // Shouldn't be walking out of bounds negative.
-            assert!(row_walk_inc >= 0 || current_rc.0 > 0);
-            assert!(col_walk_inc >= 0 || current_rc.1 > 0);
+    /// Advances the simulation by one move and tracks the move along with the new value of the cell it targets.
+    /// Returns whether the move was tracked. In strict mode, a move that is illegal is not tracked.
+    fn push_step(&mut self, mv: Move, new_cell: BoardCell) -> bool {
+        let mv_num = self.moves.len();
 
-            current_rc = RC(
-                current_rc.0.checked_add_signed(row_walk_inc).unwrap(),
-                current_rc.1.checked_add_signed(col_walk_inc).unwrap(),
-            );
+        // Once an illegal move has been found, there is no point in simulating any more moves.
+        let sim_undo = if self.first_error.is_none() {
+            info!("{:2}: state {:?}, move {:?}", mv_num, self.sim.state, mv);
+
+            let state = self.sim.state.clone();
+            match self.sim.apply(&mv) {
+                Ok(()) => Some(SimulationUndo {
+                    state,
+                    changes: std::mem::take(&mut self.sim.last_changes),
+                }),
+                Err(err) => {
+                    if self.is_strict {
+                        info!("Rejecting move {:?} in strict mode: {:?}", mv, err);
+                        return false;
+                    }
+
+                    self.first_error = Some((mv_num, err));
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let target_rc = mv.get_rc().clone();
+        let change = CellChange {
+            before: self.latest[&target_rc],
+            after: new_cell,
+            rc: target_rc,
+        };
+        self.latest[&change.rc] = change.after;
+
+        let history_node = self.history.push(self.current_history_node(), mv.clone());
+        self.moves.push(BoardStep {
+            mv,
+            change,
+            sim_undo,
+            history_node,
+        });
+
+        self.save_progress();
+        true
+    }
+
+    /// Saves the moves currently applied, if the board was set up to save its progress. See `restore_progress`.
+    fn save_progress(&self) {
+        if let Some(autosave) = &self.autosave {
+            autosave.save(self);
+        }
+    }
+
+    /// Makes a change that takes many moves or undos, saving the progress once at the end rather than after each one.
+    fn without_saving_each_step(&mut self, change: impl FnOnce(&mut Board)) {
+        let autosave = self.autosave.take();
+        change(self);
+        self.autosave = autosave;
+        self.save_progress();
+    }
+
+    /// Returns if two locations are considered adjacent to each other, according to the game's adjacency rules.
+    fn is_adjacent(grid: &BoardGrid, rc1: &RC, rc2: &RC) -> bool {
+        // A cell is not adjacent to itself, and the two must be either vertically or horizontally aligned.
+        let direction = match Direction::between(rc1, rc2) {
+            Some(direction) if !direction.is_diagonal() => direction,
+            _ => return false,
+        };
 
-            // Shouldn't be walking out of bounds positive.
-            assert!(current_rc.0 < grid.height());
-            assert!(current_rc.1 < grid.width());
+        trace!("Walk from {:?} to {:?}, going {:?}", rc1, rc2, direction);
 
+        for (current_rc, current) in grid.walk(rc1, direction) {
             // Walking has reached the end position and has found it, therefore they are adjacent.
             if current_rc == *rc2 {
                 return true;
@@ -464,46 +913,46 @@ impl Board {
 
             // This cell along the path from rc1 to rc2 is not traversible, so rc1 and rc2 are not adjacent. Generally
             // this happens because the cell is not blackened or a gap.
-            let current = grid[&current_rc];
             if !current.is_traversible_for_adjacency() {
-                log!(
+                trace!(
                     "Not connected: {:?} is not available for adjacency traversal",
                     current_rc
                 );
                 return false;
             }
         }
+
+        // Don't allow traversing out of bounds.
+        trace!(
+            "Traversed out of bounds going {:?} from {:?}",
+            direction,
+            rc1
+        );
+        false
     }
 
     /// Returns if two cells are connected for the puroses of gathering a keyword. Note that this is somewhat different
     /// than checking adjacency.
     fn is_connected_for_keyword(
         grid: &BoardGrid,
-        moves: &Vec<Move>,
+        moves: &[Move],
         rc2: &RC, // other parts considered will be rc1 (prior move) and rc0 (2 prior moves)
     ) -> bool {
         // If rc2 is the first position being considered for this path, then it's always considered connected. Later
         // positions will have to be considered for connectivity to this one.
-        if moves.len() == 0 {
+        if moves.is_empty() {
             return true;
         }
 
         let rc1 = moves.last().unwrap().get_rc();
 
-        // A location is never connected to itself.
-        if rc1 == rc2 {
-            return false;
-        }
-
-        // Must be either vertically or horizontally aligned.
-        if rc2.0 != rc1.0 && rc2.1 != rc1.1 {
-            return false;
-        }
-
-        // Figure out the direction to walk in between the previous step and the current step, assuming one of the later
-        // checks doesn't invalidate this direction.
-        let mut row_walk_inc = rc2.0.cmp(&rc1.0) as i8 as isize;
-        let mut col_walk_inc = rc2.1.cmp(&rc1.1) as i8 as isize;
+        // A location is never connected to itself, and the two must be either vertically or horizontally aligned.
+        // This is the direction to walk in between the previous step and the current step, assuming one of the later
+        // checks doesn't invalidate it.
+        let mut direction = match Direction::between(rc1, rc2) {
+            Some(direction) if !direction.is_diagonal() => direction,
+            _ => return false,
+        };
 
         // If an earlier RC, rc0, was present, it may need to be factored in to the direction of movement.
         if moves.len() >= 2 {
@@ -514,82 +963,27 @@ impl Board {
             // direction in the rc1 -> rc2 leg. However, conductors don't allow doubling back and going from rc1 back
             // towards rc0.
             if grid[rc1].is_conductor() {
-                // Determine which direction would be backtracking from rc1 towards rc0.
-                let (backtracking_row_walk_inc, backtracking_col_walk_inc) = (
-                    rc0.0.cmp(&rc1.0) as i8 as isize,
-                    rc0.1.cmp(&rc1.1) as i8 as isize,
-                );
-
-                // Don't allow backtracking.
-                if backtracking_row_walk_inc == row_walk_inc
-                    && backtracking_col_walk_inc == col_walk_inc
-                {
-                    log!("Cannot backtrack through conductor {:?}", rc1);
+                if Direction::between(rc1, rc0) == Some(direction) {
+                    trace!("Cannot backtrack through conductor {:?}", rc1);
                     return false;
                 }
             } else {
                 // If the previous RC was a regular space and not a conductor, then the direction from rc0 to rc1 must
                 // be followed to get to rc2.
-                row_walk_inc = rc1.0.cmp(&rc0.0) as i8 as isize;
-                col_walk_inc = rc1.1.cmp(&rc0.1) as i8 as isize;
+                direction = Direction::between(rc0, rc1).unwrap();
             }
-        } else {
-            // There are no keywords that would allow a conductor as the first move.
-            assert!(!grid[rc1].is_conductor());
+        } else if grid[rc1].is_conductor() {
+            // There are no keywords that would allow a conductor as the first move, but a wildcard that was gathered
+            // as the first letter can still be changed to a conductor before the keyword is finished. Its letter was
+            // already gathered, so it can't also redirect the path.
+            trace!("Cannot start a path from conductor {:?}", rc1);
+            return false;
         }
 
-        assert!(row_walk_inc == 0 || col_walk_inc == 0);
-        assert!(row_walk_inc >= -1);
-        assert!(col_walk_inc >= -1);
-        assert!(row_walk_inc <= 1);
-        assert!(col_walk_inc <= 1);
-
-        log!(
-            "Walk from {:?} to {:?}, using direction ({}, {})",
-            rc1,
-            rc2,
-            row_walk_inc,
-            col_walk_inc
-        );
+        trace!("Walk from {:?} to {:?}, going {:?}", rc1, rc2, direction);
 
         // Try to walk from rc1 towards rc2.
-        let mut current_rc = rc1.clone();
-        loop {
-            // Don't allow traversing out of bounds negative.
-            if row_walk_inc < 0 && current_rc.0 == 0 {
-                log!(
-                    "Traversed out of bounds to negative row from {:?}",
-                    current_rc
-                );
-                return false;
-            }
-
-            // Don't allow traversing out of bounds negative.
-            if col_walk_inc < 0 && current_rc.1 == 0 {
-                log!(
-                    "Traversed out of bounds to negative col from {:?}",
-                    current_rc
-                );
-                return false;
-            }
-
-            current_rc = RC(
-                current_rc.0.checked_add_signed(row_walk_inc).unwrap(),
-                current_rc.1.checked_add_signed(col_walk_inc).unwrap(),
-            );
-
-            // Don't allow traversing out of bounds positive.
-            if current_rc.0 >= grid.height() {
-                log!("Traversed beyond row bounds from {:?}", current_rc);
-                return false;
-            }
-
-            // Don't allow traversing out of bounds positive.
-            if current_rc.1 >= grid.width() {
-                log!("Traversed beyond col bounds from {:?}", current_rc);
-                return false;
-            }
-
+        for (current_rc, current) in grid.walk(rc1, direction) {
             // The traversal from rc1 to rc2 has succeeded and these two positions are considered connected.
             if current_rc == *rc2 {
                 return true;
@@ -597,368 +991,291 @@ impl Board {
 
             // Check if the current cell in the traveral is considered connected. Usually it's not when it's a cell with
             // a valid letter in it.
-            let current = grid[&current_rc];
             if !current.is_traversible_for_keyword() {
-                log!(
+                trace!(
                     "Not connected: {:?} is not available for keyword traversal",
                     current_rc
                 );
                 return false;
             }
         }
+
+        // Don't allow traversing out of bounds.
+        trace!(
+            "Traversed out of bounds going {:?} from {:?}",
+            direction,
+            rc1
+        );
+        false
     }
 
     /// Returns if a given cell is on a LOLO path (diagonal from lower-left to upper-right).
     fn is_on_lolo_path(grid: &BoardGrid, anchor_rc: &RC, target_rc: &RC) -> bool {
-        assert!(anchor_rc.0 < grid.height());
-        assert!(anchor_rc.1 < grid.width());
-        assert!(target_rc.0 < grid.height());
-        assert!(target_rc.1 < grid.width());
-
-        // Compare the position that is on the path with the new one that is being checked for being on the same path.
-        let (row_diff, col_diff) = if target_rc.0 > anchor_rc.0 {
-            // target row is higher (towards lower-left of the board), so target col should be lower (towards
-            // upper-right)
-            if target_rc.1 >= anchor_rc.1 {
-                return false;
-            }
+        // The anchor itself is not on its own path, only the cells in either direction from it.
+        grid.walk(anchor_rc, Direction::DownLeft)
+            .chain(grid.walk(anchor_rc, Direction::UpRight))
+            .any(|(rc, _)| rc == *target_rc)
+    }
+
+    /// Evaluates the moves that have been tracked so far to see if this is a valid solution. Returns `Correct` if it
+    /// is valid, or `ErrorOnMove(x, _)` where x is the 0-based move number where the solution was found to be
+    /// incorrect. For example, if the very first move is wrong, it will return `ErrorOnMove(0, _)`. If all moves are
+    /// valid but the board either still isn't complete at the end or isn't idle, then it returns one of the other
+    /// results describing why.
+    ///
+    /// The simulation is kept up to date as each move is made, so this doesn't need to replay the moves.
+    pub fn check_solution(&self) -> SolutionResult {
+        match &self.first_error {
+            None => self.sim.result(),
+            Some((mv_num, err)) => SR::ErrorOnMove(*mv_num, err.clone()),
+        }
+    }
+}
 
-            (target_rc.0 - anchor_rc.0, anchor_rc.1 - target_rc.1)
-        } else if target_rc.0 < anchor_rc.0 {
-            // target row is lower (towards upper-right of the board), so target col should be higher (towards
-            // bottom-right)
-            if target_rc.1 <= anchor_rc.1 {
-                return false;
-            }
+/// A simulation of the board and the keyword state machine, used for checking whether a sequence of moves is valid.
+#[derive(Clone)]
+struct Simulation {
+    grid: BoardGrid,
+    state: BoardState,
+    keywords: Rc<KeywordRegistry>,
 
-            (anchor_rc.0 - target_rc.0, target_rc.1 - anchor_rc.1)
-        } else {
-            // Row is equal, so it can't possibly be on a diagonal.
-            return false;
-        };
+    // The cells changed by the most recently applied move.
+    last_changes: Vec<CellChange>,
+}
+
+impl Simulation {
+    /// Creates a new simulation, starting at idle, from the given initial grid, recognizing the given keywords.
+    fn new(grid: BoardGrid, keywords: Rc<KeywordRegistry>) -> Simulation {
+        Simulation {
+            grid,
+            state: BoardState::idle(),
+            keywords,
+            last_changes: vec![],
+        }
+    }
 
-        assert!(row_diff != 0);
-        assert!(col_diff != 0);
+    /// Undoes a move that was applied to the simulation.
+    fn undo(&mut self, undo: SimulationUndo) {
+        for change in undo.changes.iter().rev() {
+            self.grid[&change.rc] = change.before;
+        }
 
-        // We've established so far that the two cells have the right rough relationship with each other: the target is
-        // somewhere to the upper-right or lower-left of the anchor_rc. Next we have to ensure that it's properly on a
-        // diagonal, which happens when the number of rows from the anchor is the same as the number of cols from it.
-        row_diff == col_diff
+        self.state = undo.state;
     }
 
-    /// Evaluates the moves that have been tracked so far to see if this is a valid solution. Returns None if it is
-    /// valid, or Some(x) where x is the 0-based move number where the solution was found to be incorrect. For example,
-    /// if the very first move is wrong, it will return `Some(0)`. Also, if all moves are valid but the board either
-    /// still isn't complete at the end or isn't idle, then it returns `Some(moves.len())`.
-    fn check_solution(&self) -> SolutionResult {
-        // Create a copy of the board that will be modified through the simulation and checked at each step for
-        // validity.
-        let mut simgrid = self.grid.clone();
+    /// Blackens a cell, keeping track of the change.
+    fn blacken_cell(&mut self, rc: &RC) {
+        let before = self.grid[rc];
+        self.grid[rc].blacken();
+        self.last_changes.push(CellChange {
+            rc: rc.clone(),
+            before,
+            after: self.grid[rc],
+        });
+    }
 
-        // The simulation starts at idle.
-        let mut state = BoardState::idle();
+    /// Attempts to change the letter in a cell, keeping track of the change. Returns whether it was permitted.
+    fn try_change_cell_letter(&mut self, rc: &RC, letter: char) -> bool {
+        let before = self.grid[rc];
+        if !self.grid[rc].try_change_letter(letter) {
+            return false;
+        }
 
-        // Iterate through all the tracked moves, checking each one for validity.
-        for (mv_num, BoardStep { mv, grid: _ }) in self.moves.iter().enumerate() {
-            log!("{:2}: state {:?}, move {:?}", mv_num, state, mv);
+        self.last_changes.push(CellChange {
+            rc: rc.clone(),
+            before,
+            after: self.grid[rc],
+        });
+        true
+    }
 
-            // `target_rc` is the location of the cell being targeted by this move. `target` is the cell itself.
-            let target_rc = mv.get_rc();
-            let target = simgrid[target_rc].clone();
+    /// Changes the letter in a cell that was ever a wildcard, which the player is permitted to do at any time other
+    /// than when a keyword says otherwise.
+    fn change_wildcard_letter(&mut self, rc: &RC, letter: char) -> Result<(), MoveError> {
+        if !self.grid[rc].was_ever_wildcard() {
+            trace!(
+                "Not allowed to change this cell's letter in state {:?}",
+                self.state
+            );
+            return Err(ME::CellCannotChangeLetterInThisState);
+        }
 
-            // None of the currently used moves, blacken, mark path, or change letter, are valid to target a cell that
-            // is already blackened. Blackened cells can be traversed for adjacency, but that's it.
-            if target.is_blackened() {
-                log!("{:?} already blackened", target_rc);
-                return SR::ErrorOnMove(mv_num, ME::AlreadyBlackened);
-            }
+        if !self.try_change_cell_letter(rc, letter) {
+            trace!("Not allowed to change letter to '{}'", letter);
+            return Err(ME::CannotChangeToThisLetter);
+        }
 
-            state = match mv {
-                // Blackening a cell has two uses:
-                // 1. when gathering a keyword, it defers blackening until the entire keyword is gathered, then the
-                //    whole keyword is blackened at once.
-                // 2. when executing a keyword, the cell is blackened right away.
-                Move::Blacken(_) => {
-                    match state {
-                        // The player is expected to gather the next letter in a keyword.
-                        BoardState::GatheringKeyword(keyword, keyword_moves) => {
-                            if !Board::is_connected_for_keyword(&simgrid, &keyword_moves, target_rc)
-                            {
-                                log!("{:?} not connected to previous keyword move", target_rc);
-                                return SR::ErrorOnMove(mv_num, ME::BlackenNotConnectedForKeyword);
-                            }
+        Ok(())
+    }
 
-                            // Keywords consist of only letters.
-                            if let Some(letter) = target.get_letter() {
-                                let mut new_keyword = keyword.clone();
-                                new_keyword.push(letter);
-
-                                // Check to see if the keyword gathered so far could possibly be one of the known
-                                // keywords. If not, the solution fails here.
-                                if !KNOWN_KEYWORDS
-                                    .iter()
-                                    .any(|known_keyword| known_keyword.starts_with(&new_keyword))
-                                {
-                                    log!("{} cannot be any known keyword", new_keyword);
-                                    return SR::ErrorOnMove(mv_num, ME::UnknownKeyword);
-                                }
+    /// Returns the state to go to after a move while executing a keyword, depending on whether the keyword is done.
+    fn state_after_execution(&self, id: KeywordId, progress: ExecutionProgress) -> BoardState {
+        if self.keywords.get(id).is_complete(&self.grid, &progress) {
+            BoardState::idle()
+        } else {
+            BoardState::ExecutingKeyword(id, progress)
+        }
+    }
 
-                                // So far this is a possible keyword, so accept the latest move.
-                                let mut new_keyword_moves = keyword_moves.clone();
-                                new_keyword_moves.push(mv.clone());
-
-                                // If the keyword so far matches a known keyword, then accept it and transition to the
-                                // executing state. Otherwise, continue gathering.
-                                if let Some(known_keyword) = KNOWN_KEYWORDS
-                                    .iter()
-                                    .find(|known_keyword| new_keyword == **known_keyword)
-                                {
-                                    // Have now accumulated a whole keyword. Black it out.
-                                    for mv in new_keyword_moves.iter() {
-                                        if let Move::Blacken(rc) = mv {
-                                            simgrid[rc].blacken();
-                                        }
-                                    }
+    /// Applies a single move to the simulation. If the move is illegal, returns the reason why, and the simulation is
+    /// left unchanged.
+    fn apply(&mut self, mv: &Move) -> Result<(), MoveError> {
+        self.last_changes.clear();
 
-                                    // Transition to the "executing" state, where the next moves are expected to
-                                    // fulfill a different condition according to which keyword was just found.
-                                    match *known_keyword {
-                                        "LOK" => BoardState::ExecutingLOK,
-                                        "TLAK" => BoardState::ExecutingTLAK(None),
-                                        "TA" => BoardState::ExecutingTA(None),
-                                        "BE" => BoardState::ExecutingBE,
-                                        "LOLO" => BoardState::ExecutingLOLO(None),
-                                        _ => {
-                                            panic!("Impossible unknown keyword {}", *known_keyword)
-                                        }
-                                    }
-                                } else {
-                                    // Next state is still gathering keywords, but including the most recently gathered
-                                    // letter.
-                                    BoardState::GatheringKeyword(new_keyword, new_keyword_moves)
-                                }
-                            } else {
-                                log!("Not a letter: {}", target.get_raw());
-                                return SR::ErrorOnMove(mv_num, ME::GatheringNonLetter);
-                            }
-                        }
-                        BoardState::ExecutingLOK => {
-                            // For executing LOK, the player is expected to blacken exactly one cell.
-                            assert!(!target.is_blackened());
-                            simgrid[target_rc].blacken();
-                            BoardState::idle()
-                        }
-                        BoardState::ExecutingTLAK(exec_rc_opt) => {
-                            // For executing TLAK, the player is expected to blacken two adjacent cells.
-
-                            // If this is the second cell, make sure it is adjacent to the first cell.
-                            if let Some(ref last_exec_rc) = exec_rc_opt {
-                                if !Board::is_adjacent(&simgrid, &last_exec_rc, target_rc) {
-                                    log!(
-                                        "{:?} not adjacent to {:?} for TLAK blacken",
-                                        last_exec_rc,
-                                        target_rc
-                                    );
-
-                                    return SR::ErrorOnMove(mv_num, ME::TLAKNotAdjacent);
-                                }
-                            }
+        // `target_rc` is the location of the cell being targeted by this move. `target` is the cell itself.
+        let target_rc = mv.get_rc();
+        let target = self.grid[target_rc];
 
-                            assert!(!target.is_blackened());
-                            simgrid[target_rc].blacken();
+        // None of the currently used moves, blacken, mark path, or change letter, are valid to target a cell that
+        // is already blackened. Blackened cells can be traversed for adjacency, but that's it.
+        if target.is_blackened() {
+            trace!("{:?} already blackened", target_rc);
+            return Err(ME::AlreadyBlackened);
+        }
 
-                            if exec_rc_opt.is_some() {
-                                BoardState::idle()
-                            } else {
-                                BoardState::ExecutingTLAK(Some(target_rc.clone()))
-                            }
+        // Keywords are given the whole simulation to change while executing, so hold onto the registry separately.
+        let keywords = self.keywords.clone();
+
+        self.state = match mv {
+            // Blackening a cell has two uses:
+            // 1. when gathering a keyword, it defers blackening until the entire keyword is gathered, then the
+            //    whole keyword is blackened at once.
+            // 2. when executing a keyword, the cell is blackened right away.
+            Move::Blacken(_) => {
+                match self.state.clone() {
+                    // The player is expected to gather the next letter in a keyword.
+                    BoardState::GatheringKeyword(keyword, keyword_moves) => {
+                        if !keywords.can_gather(&keyword, &self.grid, &keyword_moves, target_rc) {
+                            trace!("{:?} not connected to previous keyword move", target_rc);
+                            return Err(ME::BlackenNotConnectedForKeyword);
                         }
-                        BoardState::ExecutingTA(chosen_letter_opt) => {
-                            // For executing TA, the player chooses one letter and has to black out all the cells with
-                            // that letter.
-
-                            if let Some(letter) = target.get_letter_or_blank() {
-                                // If the user has chosen a letter from a previous move during this execution, make sure
-                                // the new letter being chosen matches it.
-                                if let Some(chosen_letter) = chosen_letter_opt {
-                                    if letter != chosen_letter {
-                                        log!(
-                                            "Letter {} does not match TA chosen letter {}",
-                                            letter,
-                                            chosen_letter
-                                        );
-
-                                        return SR::ErrorOnMove(mv_num, ME::TALetterMismatch);
-                                    }
-                                } else {
-                                    log!("TA choosing letter {}", letter);
-                                }
-
-                                assert!(!target.is_blackened());
-                                simgrid[target_rc].blacken();
-
-                                // If there are any more of this chosen letter on the board, then the state is still
-                                // waiting for those to be blackened out. Otherwise, the TA is done.
-                                let mut has_completed_all_letters = true;
-                                for (rc, cell) in simgrid.enumerate_row_col() {
-                                    if cell.is_blackened() {
-                                        continue;
-                                    }
 
-                                    if let Some(cell_letter) = cell.get_letter_or_blank() {
-                                        if cell_letter == letter {
-                                            log!("{:?} is still {}", rc, letter);
-                                            has_completed_all_letters = false;
-                                            break;
-                                        }
-                                    }
-                                }
+                        // Keywords consist of only letters.
+                        if let Some(letter) = target.get_letter() {
+                            let mut new_keyword = keyword.clone();
+                            new_keyword.push(letter);
 
-                                if has_completed_all_letters {
-                                    BoardState::idle()
-                                } else {
-                                    BoardState::ExecutingTA(Some(letter))
-                                }
-                            } else {
-                                log!("Not a letter: {}", target.get_raw());
-                                return SR::ErrorOnMove(mv_num, ME::TAInvalidLetter);
+                            // Check to see if the keyword gathered so far could possibly be one of the known
+                            // keywords. If not, the solution fails here.
+                            if !keywords.could_start_with(&new_keyword) {
+                                trace!("{} cannot be any known keyword", new_keyword);
+                                return Err(ME::UnknownKeyword);
                             }
-                        }
-                        BoardState::ExecutingBE => {
-                            log!("Cannot blacken while executing BE");
-                            return SR::ErrorOnMove(mv_num, ME::BECannotBlacken);
-                        }
-                        BoardState::ExecutingLOLO(anchor_rc_opt) => {
-                            // For executing LOLO, the player is expected to choose one non-blackened cell and then go
-                            // on to blacken all cells along that diagonal, from bottom-left to upper-right. Order of
-                            // blackening doesn't matter.
-                            let anchor_rc = if let Some(anchor_rc) = anchor_rc_opt {
-                                if !Board::is_on_lolo_path(&simgrid, &anchor_rc, target_rc) {
-                                    log!("{:?} is not on LOLO path", target_rc);
-                                    return SR::ErrorOnMove(mv_num, ME::LOLONotOnPath);
-                                }
-
-                                assert!(!target.is_blackened());
-                                simgrid[target_rc].blacken();
-                                anchor_rc.clone()
-                            } else {
-                                assert!(!target.is_blackened());
-                                simgrid[target_rc].blacken();
-                                target_rc.clone()
-                            };
-
-                            // Scan the board and see if any cells on the diagonal path are not done yet. All cells on
-                            // the diagonal must be done before the execution can stop.
-                            let mut has_completed_lolo_path = true;
-                            for (rc, cell) in simgrid.enumerate_row_col() {
-                                if !Board::is_on_lolo_path(&simgrid, &anchor_rc, &rc) {
-                                    continue;
-                                }
 
-                                if !cell.is_done() {
-                                    log!(
-                                        "{:?} on LOLO path including {:?} is still not done",
-                                        rc,
-                                        anchor_rc
-                                    );
-                                    has_completed_lolo_path = false;
-                                    break;
+                            // So far this is a possible keyword, so accept the latest move.
+                            let mut new_keyword_moves = keyword_moves.clone();
+                            new_keyword_moves.push(mv.clone());
+
+                            // If the keyword so far matches a known keyword, then accept it and transition to the
+                            // executing state. Otherwise, continue gathering.
+                            if let Some(id) = keywords.find(&new_keyword) {
+                                // Have now accumulated a whole keyword. Black it out.
+                                for mv in new_keyword_moves.iter() {
+                                    if let Move::Blacken(rc) = mv {
+                                        self.blacken_cell(rc);
+                                    }
                                 }
-                            }
 
-                            if has_completed_lolo_path {
-                                BoardState::idle()
+                                // Transition to the "executing" state, where the next moves are expected to
+                                // fulfill a different condition according to which keyword was just found.
+                                BoardState::ExecutingKeyword(id, ExecutionProgress::Started)
                             } else {
-                                BoardState::ExecutingLOLO(Some(anchor_rc))
+                                // Next state is still gathering keywords, but including the most recently gathered
+                                // letter.
+                                BoardState::GatheringKeyword(new_keyword, new_keyword_moves)
                             }
+                        } else {
+                            trace!("Not a letter: {}", target.get_raw());
+                            return Err(ME::GatheringNonLetter);
                         }
                     }
-                }
-                Move::MarkPath(_) => match state {
-                    BoardState::GatheringKeyword(keyword, keyword_moves) => {
-                        // Mark Path is used for conductors. The player is expected to mark whenever going to a
-                        // conductor that will redirect outside simple straight-line connectivity.
-
-                        // If the cell being marked is not connected to the previous cell in the path, then it can't be
-                        // used as part of this path.
-                        if !Board::is_connected_for_keyword(&simgrid, &keyword_moves, target_rc) {
-                            log!("{:?} not connected to previous keyword move", target_rc);
-                            return SR::ErrorOnMove(mv_num, ME::PathNotConnectedForKeyword);
-                        }
-
-                        let mut new_keyword_moves = keyword_moves.clone();
-                        new_keyword_moves.push(mv.clone());
-                        BoardState::GatheringKeyword(keyword.clone(), new_keyword_moves)
+                    BoardState::ExecutingKeyword(id, progress) => {
+                        let progress = keywords.get(id).execute_blacken(
+                            &mut ExecutionContext::new(self),
+                            &progress,
+                            target_rc,
+                        )?;
+                        self.state_after_execution(id, progress)
                     }
-                    BoardState::ExecutingLOK
-                    | BoardState::ExecutingTLAK(_)
-                    | BoardState::ExecutingTA(_)
-                    | BoardState::ExecutingBE
-                    | BoardState::ExecutingLOLO(_) => {
-                        log!("Cannot mark path while executing a keyword");
-                        return SR::ErrorOnMove(mv_num, ME::CannotMarkWhileExecuting);
+                }
+            }
+            Move::MarkPath(_) => match self.state.clone() {
+                BoardState::GatheringKeyword(keyword, keyword_moves) => {
+                    // Mark Path is used for conductors. The player is expected to mark whenever going to a
+                    // conductor that will redirect outside simple straight-line connectivity.
+
+                    // If the cell being marked is not connected to the previous cell in the path, then it can't be
+                    // used as part of this path.
+                    if !keywords.can_gather(&keyword, &self.grid, &keyword_moves, target_rc) {
+                        trace!("{:?} not connected to previous keyword move", target_rc);
+                        return Err(ME::PathNotConnectedForKeyword);
                     }
-                },
-                Move::ChangeLetter(_, letter) => match state {
-                    BoardState::GatheringKeyword(_, _)
-                    | BoardState::ExecutingLOK
-                    | BoardState::ExecutingTLAK(_)
-                    | BoardState::ExecutingTA(_)
-                    | BoardState::ExecutingLOLO(_) => {
-                        // The player is permitted to change the letter of any cell at any time, provided that cell had
-                        // a wildcard at some point in the past.
-                        if target.was_ever_wildcard() {
-                            if !simgrid[target_rc].try_change_letter(*letter) {
-                                log!("Not allowed to change letter to '{}'", letter);
-                                return SR::ErrorOnMove(mv_num, ME::CannotChangeToThisLetter);
-                            }
 
-                            state
-                        } else {
-                            log!(
-                                "Not allowed to change this cell's letter in state {:?}",
-                                state
-                            );
-                            return SR::ErrorOnMove(mv_num, ME::CellCannotChangeLetterInThisState);
-                        }
+                    let mut new_keyword_moves = keyword_moves.clone();
+                    new_keyword_moves.push(mv.clone());
+                    BoardState::GatheringKeyword(keyword.clone(), new_keyword_moves)
+                }
+                BoardState::ExecutingKeyword(_, _) => {
+                    trace!("Cannot mark path while executing a keyword");
+                    return Err(ME::CannotMarkWhileExecuting);
+                }
+            },
+            Move::ChangeLetter(_, letter) => match self.state.clone() {
+                BoardState::GatheringKeyword(_, _) => {
+                    // The player is permitted to change the letter of any cell at any time, provided that cell had
+                    // a wildcard at some point in the past.
+                    self.change_wildcard_letter(target_rc, *letter)?;
+                    self.state.clone()
+                }
+                BoardState::ExecutingKeyword(id, progress) => {
+                    // Some keywords, like BE, are executed by changing letters. The rest permit the same wildcard
+                    // changes as when gathering, which don't count towards executing the keyword, so they can't
+                    // finish it either.
+                    let new_progress = keywords.get(id).execute_change_letter(
+                        &mut ExecutionContext::new(self),
+                        &progress,
+                        target_rc,
+                        *letter,
+                    )?;
+                    if new_progress == progress {
+                        self.state.clone()
+                    } else {
+                        self.state_after_execution(id, new_progress)
                     }
-                    BoardState::ExecutingBE => {
-                        // BE requires the target cell to be blank.
-                        if !target.is_blank() {
-                            log!(
-                                "Not allowed to change letter in non-blank cell: {:?}",
-                                target.get_letter()
-                            );
-                            return SR::ErrorOnMove(mv_num, ME::BECannotChangeNonBlankCell);
-                        }
+                }
+            },
+        };
 
-                        if *letter == BLANK_LETTER || !simgrid[target_rc].try_change_letter(*letter)
-                        {
-                            log!("Not allowed to change letter to '{}'", letter);
-                            return SR::ErrorOnMove(mv_num, ME::BECannotChangeToThisLetter);
-                        }
+        Ok(())
+    }
 
-                        BoardState::idle()
-                    }
-                },
-            };
-        }
+    /// Returns true if the simulation is idle, i.e. not in the middle of gathering or executing a keyword.
+    fn is_idle(&self) -> bool {
+        matches!(&self.state, BoardState::GatheringKeyword(keyword, _) if keyword.is_empty())
+    }
+
+    /// Returns true if every cell on the board has been completed.
+    fn is_complete(&self) -> bool {
+        self.grid.iter().all(BoardCell::is_done)
+    }
 
+    /// Determines the result of the solution at this point in the simulation, assuming all moves so far were legal.
+    fn result(&self) -> SolutionResult {
         // Must be back in the idle state before considering the board to be done.
-        if let BoardState::GatheringKeyword(keyword, _) = state {
+        if let BoardState::GatheringKeyword(keyword, _) = &self.state {
             if !keyword.is_empty() {
-                log!("Partial keyword {} found. Not done.", keyword);
+                info!("Partial keyword {} found. Not done.", keyword);
                 return SR::PartialKeyword;
             }
 
-            for (rc, cell) in simgrid.enumerate_row_col() {
+            for (rc, cell) in self.grid.enumerate_row_col() {
                 if !cell.is_done() {
-                    log!("{:?} not done", rc);
+                    info!("{:?} not done", rc);
                     return SR::Incomplete;
                 }
             }
         } else {
-            log!("State {:?} is not idle", state);
+            info!("State {:?} is not idle", self.state);
             return SR::NotIdle;
         }
 
@@ -979,107 +1296,465 @@ mod tests {
         .is_err());
     }
 
+    #[test]
+    fn board_gen_leading_empty_lines() {
+        let board = Board::new("\n\nLOK").unwrap();
+        assert_eq!((board.width(), board.height()), (3, 3));
+        assert!(board.get(1, 2).unwrap().is_interactive());
+        assert_eq!(board.get(1, 2).unwrap().get_display(), ' ');
+        assert_eq!(board.get(2, 0).unwrap().get_display(), 'L');
+
+        assert!(Board::new("\nLOK\n\nLOK").is_err());
+    }
+
+    #[test]
+    fn board_gen_only_empty_lines() {
+        // A board with rows but no cols can't be played or saved, so this is an error rather than a 0x2 board.
+        assert_eq!(
+            Board::new("\n\n").err().unwrap(),
+            "There are 2 rows, but none of them have any cols!"
+        );
+        let empty = Board::new("").unwrap();
+        assert_eq!((empty.width(), empty.height()), (0, 0));
+    }
+
+    #[test]
+    fn board_gen_non_ascii() {
+        let err = Board::new(
+            "LOK\n\
+             L\u{d6}K",
+        )
+        .err()
+        .unwrap();
+        assert!(err.contains("Row 1 col 1"));
+    }
+
+    #[test]
+    fn raw_cell_non_ascii() {
+        assert!(BoardCell::raw('\u{1f600}').is_err());
+        assert!(BoardCell::raw('l').unwrap().get_letter() == Some('L'));
+        assert!(BoardCell::raw(GAP_LETTER).unwrap().get_letter().is_none());
+    }
+
+    #[test]
+    fn walks_stop_at_the_edge() {
+        let board = Board::new(
+            "L-K\n\
+             -O-",
+        )
+        .unwrap();
+        assert!(Board::is_adjacent(&board.grid, &RC(0, 0), &RC(0, 2)));
+        assert!(!Board::is_adjacent(&board.grid, &RC(0, 0), &RC(0, 3)));
+        assert!(!Board::is_adjacent(&board.grid, &RC(0, 2), &RC(5, 2)));
+        assert!(Board::is_on_lolo_path(&board.grid, &RC(1, 1), &RC(0, 2)));
+        assert!(!Board::is_on_lolo_path(&board.grid, &RC(1, 1), &RC(2, 0)));
+    }
+
+    #[test]
+    fn out_of_bounds() {
+        let mut board = Board::new(
+            "LOK_\n\
+             LOK_",
+        )
+        .unwrap();
+        assert!(board.get(2, 0).is_err());
+        assert!(board.get(0, 4).is_err());
+        assert!(board.blacken(2, 0).is_err());
+        assert!(board.mark_path(0, 4).is_err());
+        assert!(board.change_letter(5, 5, 'L').is_err());
+        assert_eq!(board.move_count(), 0);
+    }
+
+    #[test]
+    fn change_letter_unsupported_character() {
+        let mut board = Board::new("?").unwrap();
+        assert!(board.change_letter(0, 0, '\u{1f600}').is_err());
+        assert!(board.change_letter(0, 0, ' ').is_err());
+        assert!(board.change_letter(0, 0, 'L').unwrap());
+        assert_eq!(board.get(0, 0).unwrap().get_display(), 'L');
+    }
+
+    #[test]
+    fn first_letter_changed_to_conductor() {
+        let mut board = Board::new("?OK_").unwrap();
+        board.change_letter(0, 0, 'L').unwrap();
+        board.blacken(0, 0).unwrap();
+        board.change_letter(0, 0, 'X').unwrap();
+        board.blacken(0, 1).unwrap();
+        assert_eq!(
+            board.check_solution(),
+            SR::ErrorOnMove(3, ME::BlackenNotConnectedForKeyword)
+        );
+    }
+
     #[test]
     fn lok1x4_correct() {
         let mut board = Board::new("LOK_").unwrap();
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
         assert_eq!(board.check_solution(), SR::Correct);
     }
 
     #[test]
     fn undo_then_correct() {
         let mut board = Board::new("LOK_").unwrap();
-        board.blacken(0, 0);
+        board.blacken(0, 0).unwrap();
 
-        board.blacken(0, 2);
-        board.blacken(0, 1);
-        board.blacken(0, 3);
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 3).unwrap();
         board.undo();
         board.undo();
         board.undo();
 
-        board.blacken(0, 1);
-        board.blacken(0, 2);
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
 
-        board.blacken(0, 3);
+        board.blacken(0, 3).unwrap();
         board.undo();
 
-        board.blacken(0, 3);
+        board.blacken(0, 3).unwrap();
 
         assert!(board.check());
     }
 
+    #[test]
+    fn undo_rewinds_error() {
+        let mut board = Board::new("LOK_").unwrap();
+        board.blacken(0, 1).unwrap();
+        assert_eq!(
+            board.check_solution(),
+            SR::ErrorOnMove(0, ME::UnknownKeyword)
+        );
+
+        board.undo();
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        assert_eq!(board.check_solution(), SR::NotIdle);
+
+        board.blacken(0, 3).unwrap();
+        assert_eq!(board.check_solution(), SR::Correct);
+        assert_eq!(board.moves().count(), 4);
+    }
+
+    #[test]
+    fn undo_keyword_completion() {
+        let mut board = Board::new("LOK_").unwrap();
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.undo();
+        assert!(board.get(0, 0).unwrap().is_blackened());
+        assert!(!board.get(0, 2).unwrap().is_blackened());
+
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
+        assert_eq!(board.check_solution(), SR::Correct);
+    }
+
+    #[test]
+    fn undo_all_restores_board() {
+        let mut board = Board::new(
+            "TXX\n\
+             -XX\n\
+             -AX",
+        )
+        .unwrap();
+
+        board.blacken(0, 0).unwrap();
+        for _ in 0..3 {
+            board.mark_path(0, 2).unwrap();
+            board.mark_path(1, 2).unwrap();
+            board.mark_path(1, 1).unwrap();
+            board.mark_path(0, 1).unwrap();
+        }
+        assert_eq!(board.get(0, 2).unwrap().get_mark_count(), 3);
+
+        while board.moves().count() > 0 {
+            board.undo();
+        }
+
+        for (rc, cell) in board.grid.enumerate_row_col() {
+            assert!(board.get(rc.0, rc.1).unwrap() == *cell);
+        }
+        assert_eq!(board.check_solution(), SR::Incomplete);
+    }
+
+    #[test]
+    fn redo_after_undo() {
+        let mut board = Board::new("LOK_").unwrap();
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        assert!(!board.can_redo());
+        assert!(!board.redo());
+
+        board.undo();
+        board.undo();
+        assert!(board.can_redo());
+        assert!(board.redo());
+        assert!(board.redo());
+        assert!(!board.redo());
+
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
+        assert_eq!(board.check_solution(), SR::Correct);
+    }
+
+    #[test]
+    fn branch_after_undo() {
+        let mut board = Board::new("LOK_").unwrap();
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.undo();
+        board.undo();
+
+        // Making a different move after undoing creates a second branch.
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
+        assert_eq!(board.branch_count(0), 1);
+        assert_eq!(board.branch_count(1), 2);
+        assert_eq!(board.active_branch(1), Some(1));
+        assert_eq!(
+            board.branches(1),
+            vec![&Move::Blacken(RC(0, 2)), &Move::Blacken(RC(0, 1))]
+        );
+        assert_eq!(board.branch_count(4), 0);
+        assert_eq!(board.branch_count(5), 0);
+        assert_eq!(board.check_solution(), SR::Correct);
+
+        // Switching back follows the first branch as far as it went.
+        assert!(board.switch_branch(1, 0));
+        assert_eq!(board.moves().count(), 3);
+        assert!(board.get(0, 2).unwrap().is_blackened());
+        assert!(!board.get(0, 3).unwrap().is_blackened());
+        assert_eq!(
+            board.check_solution(),
+            SR::ErrorOnMove(1, ME::BlackenNotConnectedForKeyword)
+        );
+
+        assert!(board.switch_branch(1, 1));
+        assert_eq!(board.moves().count(), 4);
+        assert_eq!(board.check_solution(), SR::Correct);
+
+        assert!(!board.switch_branch(1, 2));
+        assert!(!board.switch_branch(6, 0));
+    }
+
+    #[test]
+    fn same_move_after_undo_reuses_branch() {
+        let mut board = Board::new("LOK_").unwrap();
+        board.blacken(0, 0).unwrap();
+        board.undo();
+        board.blacken(0, 0).unwrap();
+        assert_eq!(board.branch_count(0), 1);
+    }
+
+    #[test]
+    fn export_import_round_trip() {
+        let mut board = Board::new("LO?_").unwrap();
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.change_letter(0, 2, 'k').unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
+
+        let exported = board.export_moves();
+        assert_eq!(exported, "B 0 0\nB 0 1\nC 0 2 k\nB 0 2\nB 0 3");
+
+        let mut imported = Board::new("LO?_").unwrap();
+        imported.blacken(0, 3).unwrap();
+        imported.import_moves(&exported).unwrap();
+        assert_eq!(imported.export_moves(), exported);
+        assert_eq!(imported.check_solution(), SR::Correct);
+    }
+
+    #[test]
+    fn import_comments_and_illegal_moves() {
+        let mut board = Board::new(
+            "TXA\n\
+             ___",
+        )
+        .unwrap();
+        board.set_strict(true);
+        board
+            .import_moves(
+                "# TA\n\
+                 B 0 0\n\
+                 \n\
+                 M 0 1\n\
+                 B 0 0\n",
+            )
+            .unwrap();
+        assert_eq!(board.move_count(), 3);
+        assert_eq!(
+            board.check_solution(),
+            SR::ErrorOnMove(2, ME::BlackenNotConnectedForKeyword)
+        );
+        assert!(board.is_strict);
+    }
+
+    #[test]
+    fn import_invalid() {
+        let mut board = Board::new("LOK_").unwrap();
+        board.blacken(0, 0).unwrap();
+
+        assert!(board.import_moves("B 0 4").is_err());
+        assert!(board.import_moves("B 1 0").is_err());
+        assert!(board.import_moves("B 0").is_err());
+        assert!(board.import_moves("B 0 -1").is_err());
+        assert!(board.import_moves("X 0 0").is_err());
+        assert!(board.import_moves("C 0 3 -").is_err());
+        assert!(board.import_moves("C 0 3 TA").is_err());
+        assert!(board.import_moves("C 0 3 \u{e9}").is_err());
+        assert!(board.import_moves("B 0 1\nB 0 9").is_err());
+
+        // None of the failed imports changed anything.
+        assert_eq!(board.export_moves(), "B 0 0");
+    }
+
+    #[test]
+    fn check_detailed_error_on_move() {
+        let mut board = Board::new("LKO_").unwrap();
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 2).unwrap();
+        let details = board.check_detailed();
+        assert_eq!(details.get_result(), "ErrorOnMove");
+        assert_eq!(
+            details.get_error(),
+            Some(String::from("BlackenNotConnectedForKeyword"))
+        );
+        assert_eq!(details.get_move_index(), Some(1));
+        assert_eq!((details.get_row(), details.get_col()), (Some(0), Some(2)));
+    }
+
+    #[test]
+    fn check_detailed_without_error() {
+        let mut board = Board::new("LOK__").unwrap();
+        board.blacken(0, 0).unwrap();
+        let details = board.check_detailed();
+        assert_eq!(details.get_result(), "PartialKeyword");
+        assert_eq!((details.get_row(), details.get_col()), (Some(0), Some(0)));
+
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        let details = board.check_detailed();
+        assert_eq!(details.get_result(), "NotIdle");
+        assert_eq!(details.get_row(), None);
+
+        board.blacken(0, 4).unwrap();
+        let details = board.check_detailed();
+        assert_eq!(details.get_result(), "Incomplete");
+        assert_eq!(details.get_error(), None);
+        assert_eq!(details.get_move_index(), None);
+        assert_eq!((details.get_row(), details.get_col()), (Some(0), Some(3)));
+
+        let mut board = Board::new("LOK_").unwrap();
+        board.import_moves("B 0 0\nB 0 1\nB 0 2\nB 0 3").unwrap();
+        assert_eq!(board.check_detailed().get_result(), "Correct");
+    }
+
+    #[test]
+    fn strict_rejects_illegal_moves() {
+        let mut board = Board::new("LOK_").unwrap();
+        board.set_strict(true);
+        assert!(!board.blacken(0, 1).unwrap());
+        assert!(board.blacken(0, 0).unwrap());
+        assert!(!board.blacken(0, 2).unwrap());
+        assert!(!board.mark_path(0, 2).unwrap());
+        assert!(board.blacken(0, 1).unwrap());
+        assert!(board.blacken(0, 2).unwrap());
+        assert!(!board.change_letter(0, 3, 'Q').unwrap());
+        assert!(!board.blacken(0, 0).unwrap());
+        assert!(board.blacken(0, 3).unwrap());
+        assert_eq!(board.check_solution(), SR::Correct);
+        assert_eq!(board.moves().count(), 4);
+    }
+
+    #[test]
+    fn strict_allows_moves_after_earlier_error() {
+        let mut board = Board::new("LOK_").unwrap();
+        board.blacken(0, 1).unwrap();
+        board.set_strict(true);
+        assert!(board.blacken(0, 0).unwrap());
+        assert_eq!(
+            board.check_solution(),
+            SR::ErrorOnMove(0, ME::UnknownKeyword)
+        );
+    }
+
     #[test]
     fn lok1x4_correct_non_blank() {
         let mut board = Board::new("LOKQ").unwrap();
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
         assert_eq!(board.check_solution(), SR::Correct);
     }
 
     #[test]
     fn partial_keyword() {
         let mut board = Board::new("L").unwrap();
-        board.blacken(0, 0);
+        board.blacken(0, 0).unwrap();
         assert_eq!(board.check_solution(), SR::PartialKeyword);
     }
 
     #[test]
     fn lok1x4_jump_gap() {
         let mut board = Board::new("LO-K-_").unwrap();
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 3);
-        board.blacken(0, 5);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 3).unwrap();
+        board.blacken(0, 5).unwrap();
         assert_eq!(board.check_solution(), SR::Correct);
     }
 
     #[test]
     fn lok_correct_jump_blackened() {
         let mut board = Board::new("LO_KLOK_").unwrap();
-        board.blacken(0, 4);
-        board.blacken(0, 5);
-        board.blacken(0, 6);
-        board.blacken(0, 2);
-
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 3);
-        board.blacken(0, 7);
+        board.blacken(0, 4).unwrap();
+        board.blacken(0, 5).unwrap();
+        board.blacken(0, 6).unwrap();
+        board.blacken(0, 2).unwrap();
+
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 3).unwrap();
+        board.blacken(0, 7).unwrap();
         assert_eq!(board.check_solution(), SR::Correct);
     }
 
     #[test]
     fn lok_unsolvable_cant_execute() {
         let mut board = Board::new("LOK").unwrap();
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
         assert_eq!(board.check_solution(), SR::NotIdle);
     }
 
     #[test]
     fn lok1x5_unsolvable_extra_space() {
         let mut board = Board::new("LOK__").unwrap();
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
         assert_eq!(board.check_solution(), SR::Incomplete);
     }
 
     #[test]
     fn lok1x5_unsolvable_out_of_order() {
         let mut board = Board::new("LKO_").unwrap();
-        board.blacken(0, 0);
-        board.blacken(0, 2);
-        board.blacken(0, 1);
-        board.blacken(0, 3);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 3).unwrap();
         assert_eq!(
             board.check_solution(),
             SR::ErrorOnMove(1, ME::BlackenNotConnectedForKeyword)
@@ -1089,10 +1764,10 @@ mod tests {
     #[test]
     fn lok1x4_out_of_order_middle() {
         let mut board = Board::new("LOK_").unwrap();
-        board.blacken(0, 0);
-        board.blacken(0, 2);
-        board.blacken(0, 1);
-        board.blacken(0, 3);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 3).unwrap();
         assert_eq!(
             board.check_solution(),
             SR::ErrorOnMove(1, ME::BlackenNotConnectedForKeyword)
@@ -1102,10 +1777,10 @@ mod tests {
     #[test]
     fn lok1x4_out_of_order_backwards() {
         let mut board = Board::new("LOK_").unwrap();
-        board.blacken(0, 2);
-        board.blacken(0, 1);
-        board.blacken(0, 0);
-        board.blacken(0, 3);
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 3).unwrap();
         assert_eq!(
             board.check_solution(),
             SR::ErrorOnMove(0, ME::UnknownKeyword)
@@ -1119,14 +1794,14 @@ mod tests {
              LOK_",
         )
         .unwrap();
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(1, 3);
-        board.blacken(1, 0);
-        board.blacken(1, 1);
-        board.blacken(1, 2);
-        board.blacken(0, 3);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(1, 3).unwrap();
+        board.blacken(1, 0).unwrap();
+        board.blacken(1, 1).unwrap();
+        board.blacken(1, 2).unwrap();
+        board.blacken(0, 3).unwrap();
         assert_eq!(board.check_solution(), SR::Correct);
     }
 
@@ -1137,14 +1812,14 @@ mod tests {
              LOK_",
         )
         .unwrap();
-        board.blacken(0, 0);
-        board.blacken(1, 1);
-        board.blacken(1, 2);
-        board.blacken(1, 3);
-        board.blacken(1, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
+        board.blacken(0, 0).unwrap();
+        board.blacken(1, 1).unwrap();
+        board.blacken(1, 2).unwrap();
+        board.blacken(1, 3).unwrap();
+        board.blacken(1, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
         assert_eq!(
             board.check_solution(),
             SR::ErrorOnMove(1, ME::BlackenNotConnectedForKeyword)
@@ -1159,10 +1834,10 @@ mod tests {
         )
         .unwrap();
 
-        board.blacken(0, 1);
-        board.blacken(0, 0);
-        board.blacken(1, 0);
-        board.blacken(1, 1);
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 0).unwrap();
+        board.blacken(1, 0).unwrap();
+        board.blacken(1, 1).unwrap();
 
         assert_eq!(
             board.check_solution(),
@@ -1173,11 +1848,11 @@ mod tests {
     #[test]
     fn lok_cannot_mark_path() {
         let mut board = Board::new("LOK_").unwrap();
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.mark_path(0, 3);
-        board.blacken(0, 3);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.mark_path(0, 3).unwrap();
+        board.blacken(0, 3).unwrap();
         assert_eq!(
             board.check_solution(),
             SR::ErrorOnMove(3, ME::CannotMarkWhileExecuting)
@@ -1187,11 +1862,11 @@ mod tests {
     #[test]
     fn lok_cannot_change_letter() {
         let mut board = Board::new("LOK_").unwrap();
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.change_letter(0, 3, 'Q');
-        board.blacken(0, 3);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.change_letter(0, 3, 'Q').unwrap();
+        board.blacken(0, 3).unwrap();
         assert_eq!(
             board.check_solution(),
             SR::ErrorOnMove(3, ME::CellCannotChangeLetterInThisState)
@@ -1201,48 +1876,48 @@ mod tests {
     #[test]
     fn tlak_correct_left_to_right() {
         let mut board = Board::new("TLAK__").unwrap();
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
-        board.blacken(0, 4);
-        board.blacken(0, 5);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
+        board.blacken(0, 4).unwrap();
+        board.blacken(0, 5).unwrap();
         assert_eq!(board.check_solution(), SR::Correct);
     }
 
     #[test]
     fn tlak_correct_left_to_right_big_gap() {
         let mut board = Board::new("TLAK_-----_").unwrap();
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
-        board.blacken(0, 4);
-        board.blacken(0, 10);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
+        board.blacken(0, 4).unwrap();
+        board.blacken(0, 10).unwrap();
         assert_eq!(board.check_solution(), SR::Correct);
     }
 
     #[test]
     fn tlak_correct_right_to_left() {
         let mut board = Board::new("TLAK__").unwrap();
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
-        board.blacken(0, 5);
-        board.blacken(0, 4);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
+        board.blacken(0, 5).unwrap();
+        board.blacken(0, 4).unwrap();
         assert_eq!(board.check_solution(), SR::Correct);
     }
 
     #[test]
     fn tlak_correct_right_to_left_big_gap() {
         let mut board = Board::new("TLAK_-----_").unwrap();
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
-        board.blacken(0, 10);
-        board.blacken(0, 4);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
+        board.blacken(0, 10).unwrap();
+        board.blacken(0, 4).unwrap();
         assert_eq!(board.check_solution(), SR::Correct);
     }
 
@@ -1253,12 +1928,12 @@ mod tests {
              ----_",
         )
         .unwrap();
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
-        board.blacken(0, 4);
-        board.blacken(1, 4);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
+        board.blacken(0, 4).unwrap();
+        board.blacken(1, 4).unwrap();
         assert_eq!(board.check_solution(), SR::Correct);
     }
 
@@ -1274,12 +1949,12 @@ mod tests {
              ----_",
         )
         .unwrap();
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
-        board.blacken(0, 4);
-        board.blacken(6, 4);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
+        board.blacken(0, 4).unwrap();
+        board.blacken(6, 4).unwrap();
         assert_eq!(board.check_solution(), SR::Correct);
     }
 
@@ -1290,12 +1965,12 @@ mod tests {
              ----_",
         )
         .unwrap();
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
-        board.blacken(1, 4);
-        board.blacken(0, 4);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
+        board.blacken(1, 4).unwrap();
+        board.blacken(0, 4).unwrap();
         assert_eq!(board.check_solution(), SR::Correct);
     }
 
@@ -1311,12 +1986,12 @@ mod tests {
              ----_",
         )
         .unwrap();
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
-        board.blacken(6, 4);
-        board.blacken(0, 4);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
+        board.blacken(6, 4).unwrap();
+        board.blacken(0, 4).unwrap();
         assert_eq!(board.check_solution(), SR::Correct);
     }
 
@@ -1327,12 +2002,12 @@ mod tests {
              ---_-",
         )
         .unwrap();
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
-        board.blacken(1, 3);
-        board.blacken(0, 4);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
+        board.blacken(1, 3).unwrap();
+        board.blacken(0, 4).unwrap();
         assert_eq!(
             board.check_solution(),
             SR::ErrorOnMove(5, ME::TLAKNotAdjacent)
@@ -1346,12 +2021,12 @@ mod tests {
              ---_-",
         )
         .unwrap();
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
-        board.blacken(0, 4);
-        board.blacken(1, 3);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
+        board.blacken(0, 4).unwrap();
+        board.blacken(1, 3).unwrap();
         assert_eq!(
             board.check_solution(),
             SR::ErrorOnMove(5, ME::TLAKNotAdjacent)
@@ -1365,12 +2040,12 @@ mod tests {
              -_---",
         )
         .unwrap();
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
-        board.blacken(0, 4);
-        board.blacken(0, 0);
-        board.blacken(1, 1);
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
+        board.blacken(0, 4).unwrap();
+        board.blacken(0, 0).unwrap();
+        board.blacken(1, 1).unwrap();
         assert_eq!(
             board.check_solution(),
             SR::ErrorOnMove(5, ME::TLAKNotAdjacent)
@@ -1384,12 +2059,12 @@ mod tests {
              -_---",
         )
         .unwrap();
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
-        board.blacken(0, 4);
-        board.blacken(1, 1);
-        board.blacken(0, 0);
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
+        board.blacken(0, 4).unwrap();
+        board.blacken(1, 1).unwrap();
+        board.blacken(0, 0).unwrap();
         assert_eq!(
             board.check_solution(),
             SR::ErrorOnMove(5, ME::TLAKNotAdjacent)
@@ -1399,33 +2074,33 @@ mod tests {
     #[test]
     fn tlak_cant_execute1() {
         let mut board = Board::new("TLAK").unwrap();
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
         assert_eq!(board.check_solution(), SR::NotIdle);
     }
 
     #[test]
     fn tlak_cant_execute2() {
         let mut board = Board::new("TLAK_").unwrap();
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
-        board.blacken(0, 4);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
+        board.blacken(0, 4).unwrap();
         assert_eq!(board.check_solution(), SR::NotIdle);
     }
 
     #[test]
     fn tlak_wrong_k() {
         let mut board = Board::new("TLAZ__").unwrap();
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
-        board.blacken(0, 4);
-        board.blacken(0, 5);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
+        board.blacken(0, 4).unwrap();
+        board.blacken(0, 5).unwrap();
         assert_eq!(
             board.check_solution(),
             SR::ErrorOnMove(3, ME::UnknownKeyword)
@@ -1435,25 +2110,25 @@ mod tests {
     #[test]
     fn tlak_correct_non_blank() {
         let mut board = Board::new("TLAKQQ").unwrap();
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
-        board.blacken(0, 4);
-        board.blacken(0, 5);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
+        board.blacken(0, 4).unwrap();
+        board.blacken(0, 5).unwrap();
         assert_eq!(board.check_solution(), SR::Correct);
     }
 
     #[test]
     fn tlak_cannot_mark_path() {
         let mut board = Board::new("TLAK__").unwrap();
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
-        board.blacken(0, 4);
-        board.mark_path(0, 5);
-        board.blacken(0, 5);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
+        board.blacken(0, 4).unwrap();
+        board.mark_path(0, 5).unwrap();
+        board.blacken(0, 5).unwrap();
         assert_eq!(
             board.check_solution(),
             SR::ErrorOnMove(5, ME::CannotMarkWhileExecuting)
@@ -1463,13 +2138,13 @@ mod tests {
     #[test]
     fn tlak_cannot_change_leter() {
         let mut board = Board::new("TLAK__").unwrap();
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
-        board.blacken(0, 4);
-        board.change_letter(0, 5, 'Q');
-        board.blacken(0, 5);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
+        board.blacken(0, 4).unwrap();
+        board.change_letter(0, 5, 'Q').unwrap();
+        board.blacken(0, 5).unwrap();
         assert_eq!(
             board.check_solution(),
             SR::ErrorOnMove(5, ME::CellCannotChangeLetterInThisState)
@@ -1483,10 +2158,10 @@ mod tests {
              Q-Q",
         )
         .unwrap();
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(1, 0);
-        board.blacken(1, 2);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(1, 0).unwrap();
+        board.blacken(1, 2).unwrap();
         assert_eq!(board.check_solution(), SR::Correct);
     }
 
@@ -1497,12 +2172,12 @@ mod tests {
              QQZ",
         )
         .unwrap();
-        board.blacken(0, 0);
-        board.blacken(0, 1);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
 
-        board.blacken(1, 0);
-        board.blacken(1, 2);
-        board.blacken(1, 1);
+        board.blacken(1, 0).unwrap();
+        board.blacken(1, 2).unwrap();
+        board.blacken(1, 1).unwrap();
         assert_eq!(
             board.check_solution(),
             SR::ErrorOnMove(3, ME::TALetterMismatch)
@@ -1512,18 +2187,18 @@ mod tests {
     #[test]
     fn ta_correct_blanks() {
         let mut board = Board::new("TA__").unwrap();
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
         assert_eq!(board.check_solution(), SR::Correct);
     }
 
     #[test]
     fn ta_unsolvable_no_exec() {
         let mut board = Board::new("TA--").unwrap();
-        board.blacken(0, 0);
-        board.blacken(0, 1);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
         assert_eq!(board.check_solution(), SR::NotIdle);
     }
 
@@ -1534,11 +2209,11 @@ mod tests {
              Q-Q",
         )
         .unwrap();
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(1, 0);
-        board.mark_path(1, 2);
-        board.blacken(1, 2);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(1, 0).unwrap();
+        board.mark_path(1, 2).unwrap();
+        board.blacken(1, 2).unwrap();
         assert_eq!(
             board.check_solution(),
             SR::ErrorOnMove(3, ME::CannotMarkWhileExecuting)
@@ -1552,17 +2227,43 @@ mod tests {
              Z-Q",
         )
         .unwrap();
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.change_letter(1, 0, 'Q');
-        board.blacken(1, 0);
-        board.blacken(1, 2);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.change_letter(1, 0, 'Q').unwrap();
+        board.blacken(1, 0).unwrap();
+        board.blacken(1, 2).unwrap();
         assert_eq!(
             board.check_solution(),
             SR::ErrorOnMove(2, ME::CellCannotChangeLetterInThisState)
         );
     }
 
+    #[test]
+    fn ta_changing_last_letter_does_not_finish() {
+        let mut board = Board::new(
+            "TAA?\n\
+             LOK-",
+        )
+        .unwrap();
+        board.change_letter(0, 3, 'A').unwrap();
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+
+        // Exec TA, choosing A. Changing the last A away doesn't finish the TA, so it can't be used to skip the wildcard.
+        board.blacken(0, 2).unwrap();
+        board.change_letter(0, 3, 'B').unwrap();
+
+        // LOK
+        board.blacken(1, 0).unwrap();
+        board.blacken(1, 1).unwrap();
+        board.blacken(1, 2).unwrap();
+        board.blacken(0, 3).unwrap();
+        assert_eq!(
+            board.check_solution(),
+            SR::ErrorOnMove(5, ME::TALetterMismatch)
+        );
+    }
+
     #[test]
     fn x_correct() {
         let mut board = Board::new(
@@ -1575,28 +2276,28 @@ mod tests {
         .unwrap();
 
         // TLAK
-        board.blacken(0, 0);
-        board.mark_path(0, 1);
-        board.blacken(0, 2);
-        board.mark_path(0, 3);
-        board.mark_path(2, 3);
-        board.blacken(2, 2);
-        board.mark_path(2, 1);
-        board.blacken(1, 1);
+        board.blacken(0, 0).unwrap();
+        board.mark_path(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.mark_path(0, 3).unwrap();
+        board.mark_path(2, 3).unwrap();
+        board.blacken(2, 2).unwrap();
+        board.mark_path(2, 1).unwrap();
+        board.blacken(1, 1).unwrap();
 
         // Exec TLAK
-        board.blacken(4, 2);
-        board.blacken(4, 3);
+        board.blacken(4, 2).unwrap();
+        board.blacken(4, 3).unwrap();
 
         // TA
-        board.blacken(4, 0);
-        board.blacken(4, 1);
+        board.blacken(4, 0).unwrap();
+        board.blacken(4, 1).unwrap();
 
         // Exec TA
-        board.blacken(0, 1);
-        board.blacken(0, 3);
-        board.blacken(2, 1);
-        board.blacken(2, 3);
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 3).unwrap();
+        board.blacken(2, 1).unwrap();
+        board.blacken(2, 3).unwrap();
 
         assert_eq!(board.check_solution(), SR::Correct);
     }
@@ -1606,11 +2307,11 @@ mod tests {
         let mut board = Board::new("TXA").unwrap();
 
         // TA
-        board.blacken(0, 0);
-        board.blacken(0, 2);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 2).unwrap();
 
         // Exec TA
-        board.blacken(0, 1);
+        board.blacken(0, 1).unwrap();
 
         assert_eq!(board.check_solution(), SR::Correct);
     }
@@ -1625,29 +2326,29 @@ mod tests {
         .unwrap();
 
         // T
-        board.blacken(0, 0);
+        board.blacken(0, 0).unwrap();
 
         // Loop
-        board.mark_path(0, 2);
-        board.mark_path(1, 2);
-        board.mark_path(1, 1);
-        board.mark_path(0, 1);
-        board.mark_path(0, 2);
-        board.mark_path(1, 2);
-        board.mark_path(1, 1);
-        board.mark_path(0, 1);
-        board.mark_path(0, 2);
+        board.mark_path(0, 2).unwrap();
+        board.mark_path(1, 2).unwrap();
+        board.mark_path(1, 1).unwrap();
+        board.mark_path(0, 1).unwrap();
+        board.mark_path(0, 2).unwrap();
+        board.mark_path(1, 2).unwrap();
+        board.mark_path(1, 1).unwrap();
+        board.mark_path(0, 1).unwrap();
+        board.mark_path(0, 2).unwrap();
 
         // A
-        board.mark_path(2, 2);
-        board.blacken(2, 1);
+        board.mark_path(2, 2).unwrap();
+        board.blacken(2, 1).unwrap();
 
         // Exec TA
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(1, 1);
-        board.blacken(1, 2);
-        board.blacken(2, 2);
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(1, 1).unwrap();
+        board.blacken(1, 2).unwrap();
+        board.blacken(2, 2).unwrap();
 
         assert_eq!(board.check_solution(), SR::Correct);
     }
@@ -1661,18 +2362,18 @@ mod tests {
         )
         .unwrap();
 
-        board.blacken(1, 0);
-        board.blacken(1, 1);
-        board.mark_path(1, 2);
-        board.mark_path(2, 2);
+        board.blacken(1, 0).unwrap();
+        board.blacken(1, 1).unwrap();
+        board.mark_path(1, 2).unwrap();
+        board.mark_path(2, 2).unwrap();
 
         // Reversal not allowed
-        board.mark_path(0, 2);
+        board.mark_path(0, 2).unwrap();
 
-        board.blacken(0, 0);
+        board.blacken(0, 0).unwrap();
 
         // Exec LOK
-        board.blacken(0, 0);
+        board.blacken(0, 0).unwrap();
 
         assert_eq!(
             board.check_solution(),
@@ -1689,17 +2390,17 @@ mod tests {
         )
         .unwrap();
 
-        board.blacken(1, 0);
-        board.blacken(1, 1);
-        board.mark_path(1, 2);
-        board.mark_path(0, 2);
+        board.blacken(1, 0).unwrap();
+        board.blacken(1, 1).unwrap();
+        board.mark_path(1, 2).unwrap();
+        board.mark_path(0, 2).unwrap();
 
         // Reversal not allowed
-        board.mark_path(2, 2);
-        board.blacken(2, 0);
+        board.mark_path(2, 2).unwrap();
+        board.blacken(2, 0).unwrap();
 
         // Exec LOK
-        board.blacken(0, 0);
+        board.blacken(0, 0).unwrap();
 
         assert_eq!(
             board.check_solution(),
@@ -1716,17 +2417,17 @@ mod tests {
         )
         .unwrap();
 
-        board.blacken(0, 1);
-        board.blacken(1, 1);
-        board.mark_path(2, 1);
-        board.mark_path(2, 2);
+        board.blacken(0, 1).unwrap();
+        board.blacken(1, 1).unwrap();
+        board.mark_path(2, 1).unwrap();
+        board.mark_path(2, 2).unwrap();
 
         // Reversal not allowed
-        board.mark_path(2, 0);
-        board.blacken(0, 0);
+        board.mark_path(2, 0).unwrap();
+        board.blacken(0, 0).unwrap();
 
         // Exec LOK
-        board.blacken(0, 0);
+        board.blacken(0, 0).unwrap();
 
         assert_eq!(
             board.check_solution(),
@@ -1743,17 +2444,17 @@ mod tests {
         )
         .unwrap();
 
-        board.blacken(0, 1);
-        board.blacken(1, 1);
-        board.mark_path(2, 1);
-        board.mark_path(2, 0);
+        board.blacken(0, 1).unwrap();
+        board.blacken(1, 1).unwrap();
+        board.mark_path(2, 1).unwrap();
+        board.mark_path(2, 0).unwrap();
 
         // Reversal not allowed
-        board.mark_path(2, 2);
-        board.blacken(0, 2);
+        board.mark_path(2, 2).unwrap();
+        board.blacken(0, 2).unwrap();
 
         // Exec LOK
-        board.blacken(0, 0);
+        board.blacken(0, 0).unwrap();
 
         assert_eq!(
             board.check_solution(),
@@ -1770,16 +2471,16 @@ mod tests {
         )
         .unwrap();
 
-        board.blacken(1, 0);
-        board.blacken(1, 1);
-        board.mark_path(1, 2);
-        board.mark_path(2, 2);
+        board.blacken(1, 0).unwrap();
+        board.blacken(1, 1).unwrap();
+        board.mark_path(1, 2).unwrap();
+        board.mark_path(2, 2).unwrap();
 
         // Reversal not allowed
-        board.blacken(0, 2);
+        board.blacken(0, 2).unwrap();
 
         // Exec LOK
-        board.blacken(0, 0);
+        board.blacken(0, 0).unwrap();
 
         assert_eq!(
             board.check_solution(),
@@ -1796,16 +2497,16 @@ mod tests {
         )
         .unwrap();
 
-        board.blacken(1, 0);
-        board.blacken(1, 1);
-        board.mark_path(1, 2);
-        board.mark_path(0, 2);
+        board.blacken(1, 0).unwrap();
+        board.blacken(1, 1).unwrap();
+        board.mark_path(1, 2).unwrap();
+        board.mark_path(0, 2).unwrap();
 
         // Reversal not allowed
-        board.blacken(2, 2);
+        board.blacken(2, 2).unwrap();
 
         // Exec LOK
-        board.blacken(0, 0);
+        board.blacken(0, 0).unwrap();
 
         assert_eq!(
             board.check_solution(),
@@ -1822,16 +2523,16 @@ mod tests {
         )
         .unwrap();
 
-        board.blacken(0, 1);
-        board.blacken(1, 1);
-        board.mark_path(2, 1);
-        board.mark_path(2, 2);
+        board.blacken(0, 1).unwrap();
+        board.blacken(1, 1).unwrap();
+        board.mark_path(2, 1).unwrap();
+        board.mark_path(2, 2).unwrap();
 
         // Reversal not allowed
-        board.blacken(2, 0);
+        board.blacken(2, 0).unwrap();
 
         // Exec LOK
-        board.blacken(0, 0);
+        board.blacken(0, 0).unwrap();
 
         assert_eq!(
             board.check_solution(),
@@ -1848,16 +2549,16 @@ mod tests {
         )
         .unwrap();
 
-        board.blacken(0, 1);
-        board.blacken(1, 1);
-        board.mark_path(2, 1);
-        board.mark_path(2, 0);
+        board.blacken(0, 1).unwrap();
+        board.blacken(1, 1).unwrap();
+        board.mark_path(2, 1).unwrap();
+        board.mark_path(2, 0).unwrap();
 
         // Reversal not allowed
-        board.blacken(2, 2);
+        board.blacken(2, 2).unwrap();
 
         // Exec LOK
-        board.blacken(0, 0);
+        board.blacken(0, 0).unwrap();
 
         assert_eq!(
             board.check_solution(),
@@ -1870,22 +2571,22 @@ mod tests {
         let mut board = Board::new("TLAK_X_LOK").unwrap();
 
         // TLAK
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
 
         // Exec TLAK, but these aren't adjacent because conductor
-        board.blacken(0, 4);
-        board.blacken(0, 6);
+        board.blacken(0, 4).unwrap();
+        board.blacken(0, 6).unwrap();
 
         // LOK
-        board.blacken(0, 7);
-        board.blacken(0, 8);
-        board.blacken(0, 9);
+        board.blacken(0, 7).unwrap();
+        board.blacken(0, 8).unwrap();
+        board.blacken(0, 9).unwrap();
 
         // Exec LOK
-        board.blacken(0, 5);
+        board.blacken(0, 5).unwrap();
 
         assert_eq!(
             board.check_solution(),
@@ -1898,18 +2599,18 @@ mod tests {
         let mut board = Board::new("BEA_Z").unwrap();
 
         // BE
-        board.blacken(0, 0);
-        board.blacken(0, 1);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
 
         // Exec BE
-        board.change_letter(0, 3, 't');
+        board.change_letter(0, 3, 't').unwrap();
 
         // TA
-        board.blacken(0, 3);
-        board.blacken(0, 2);
+        board.blacken(0, 3).unwrap();
+        board.blacken(0, 2).unwrap();
 
         // Exec TA
-        board.blacken(0, 4);
+        board.blacken(0, 4).unwrap();
         assert_eq!(board.check_solution(), SR::Correct);
     }
 
@@ -1918,8 +2619,8 @@ mod tests {
         let mut board = Board::new("BE-").unwrap();
 
         // BE
-        board.blacken(0, 0);
-        board.blacken(0, 1);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
 
         assert_eq!(board.check_solution(), SR::NotIdle);
     }
@@ -1929,11 +2630,11 @@ mod tests {
         let mut board = Board::new("BEZ").unwrap();
 
         // BE
-        board.blacken(0, 0);
-        board.blacken(0, 1);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
 
         // Exec BE, but not allowed to change regular cell
-        board.change_letter(0, 2, 'Q');
+        board.change_letter(0, 2, 'Q').unwrap();
         assert_eq!(
             board.check_solution(),
             SR::ErrorOnMove(2, ME::BECannotChangeNonBlankCell)
@@ -1945,18 +2646,18 @@ mod tests {
         let mut board = Board::new("BEBE_").unwrap();
 
         // BE
-        board.blacken(0, 0);
-        board.blacken(0, 1);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
 
         // Exec BE
-        board.change_letter(0, 4, 'Z');
+        board.change_letter(0, 4, 'Z').unwrap();
 
         // BE
-        board.blacken(0, 2);
-        board.blacken(0, 3);
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
 
         // Exec BE, but not allowed to change letter of a blackened cell
-        board.change_letter(0, 0, 'Z');
+        board.change_letter(0, 0, 'Z').unwrap();
         assert_eq!(
             board.check_solution(),
             SR::ErrorOnMove(5, ME::AlreadyBlackened)
@@ -1968,19 +2669,19 @@ mod tests {
         let mut board = Board::new("BEA_Z").unwrap();
 
         // BE
-        board.blacken(0, 0);
-        board.blacken(0, 1);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
 
         // Exec BE, but blacken is not allowed
-        board.blacken(0, 3);
-        board.change_letter(0, 3, 't');
+        board.blacken(0, 3).unwrap();
+        board.change_letter(0, 3, 't').unwrap();
 
         // TA
-        board.blacken(0, 3);
-        board.blacken(0, 2);
+        board.blacken(0, 3).unwrap();
+        board.blacken(0, 2).unwrap();
 
         // Exec TA
-        board.blacken(0, 4);
+        board.blacken(0, 4).unwrap();
         assert_eq!(
             board.check_solution(),
             SR::ErrorOnMove(2, ME::BECannotBlacken)
@@ -1992,19 +2693,19 @@ mod tests {
         let mut board = Board::new("BEA_Z").unwrap();
 
         // BE
-        board.blacken(0, 0);
-        board.blacken(0, 1);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
 
         // Exec BE, but blacken is not allowed
-        board.mark_path(0, 3);
-        board.change_letter(0, 3, 't');
+        board.mark_path(0, 3).unwrap();
+        board.change_letter(0, 3, 't').unwrap();
 
         // TA
-        board.blacken(0, 3);
-        board.blacken(0, 2);
+        board.blacken(0, 3).unwrap();
+        board.blacken(0, 2).unwrap();
 
         // Exec TA
-        board.blacken(0, 4);
+        board.blacken(0, 4).unwrap();
         assert_eq!(
             board.check_solution(),
             SR::ErrorOnMove(2, ME::CannotMarkWhileExecuting)
@@ -2016,26 +2717,26 @@ mod tests {
         let mut board = Board::new("BEBE_OK_").unwrap();
 
         // BE
-        board.blacken(0, 0);
-        board.blacken(0, 1);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
 
         // Exec BE, but underscore not allowed
-        board.change_letter(0, 4, BLANK_LETTER);
+        board.change_letter(0, 4, BLANK_LETTER).unwrap();
 
         // BE
-        board.blacken(0, 2);
-        board.blacken(0, 3);
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
 
         // Exec BE
-        board.change_letter(0, 4, 'L');
+        board.change_letter(0, 4, 'L').unwrap();
 
         // LOK
-        board.blacken(0, 4);
-        board.blacken(0, 5);
-        board.blacken(0, 6);
+        board.blacken(0, 4).unwrap();
+        board.blacken(0, 5).unwrap();
+        board.blacken(0, 6).unwrap();
 
         // Exec LOK
-        board.blacken(0, 7);
+        board.blacken(0, 7).unwrap();
         assert_eq!(
             board.check_solution(),
             SR::ErrorOnMove(2, ME::BECannotChangeToThisLetter)
@@ -2047,19 +2748,19 @@ mod tests {
         let mut board = Board::new("BEL_OK_").unwrap();
 
         // BE
-        board.blacken(0, 0);
-        board.blacken(0, 1);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
 
         // Exec BE, but dash not allowed, so this is not even counted as a move.
-        board.change_letter(0, 3, GAP_LETTER);
+        board.change_letter(0, 3, GAP_LETTER).unwrap();
 
         // LOK
-        board.blacken(0, 2);
-        board.blacken(0, 4);
-        board.blacken(0, 5);
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 4).unwrap();
+        board.blacken(0, 5).unwrap();
 
         // Exec LOK
-        board.blacken(0, 6);
+        board.blacken(0, 6).unwrap();
         assert_eq!(
             board.check_solution(),
             SR::ErrorOnMove(2, ME::BECannotBlacken)
@@ -2075,20 +2776,20 @@ mod tests {
         .unwrap();
 
         // T
-        board.change_letter(0, 0, 'T');
-        board.blacken(0, 0);
-        board.mark_path(0, 1);
-        board.mark_path(1, 1);
-        board.mark_path(1, 0);
+        board.change_letter(0, 0, 'T').unwrap();
+        board.blacken(0, 0).unwrap();
+        board.mark_path(0, 1).unwrap();
+        board.mark_path(1, 1).unwrap();
+        board.mark_path(1, 0).unwrap();
 
         // A
-        board.change_letter(0, 0, 'A');
-        board.blacken(0, 0);
+        board.change_letter(0, 0, 'A').unwrap();
+        board.blacken(0, 0).unwrap();
 
         // Exec TA
-        board.blacken(0, 1);
-        board.blacken(1, 0);
-        board.blacken(1, 1);
+        board.blacken(0, 1).unwrap();
+        board.blacken(1, 0).unwrap();
+        board.blacken(1, 1).unwrap();
 
         assert_eq!(board.check_solution(), SR::Correct);
     }
@@ -2102,14 +2803,14 @@ mod tests {
         .unwrap();
 
         // LOK
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.change_letter(0, 2, CONDUCTOR_LETTER);
-        board.mark_path(0, 2);
-        board.blacken(1, 2);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.change_letter(0, 2, CONDUCTOR_LETTER).unwrap();
+        board.mark_path(0, 2).unwrap();
+        board.blacken(1, 2).unwrap();
 
         // Exec LOK
-        board.blacken(0, 2);
+        board.blacken(0, 2).unwrap();
 
         assert_eq!(board.check_solution(), SR::Correct);
     }
@@ -2119,15 +2820,15 @@ mod tests {
         let mut board = Board::new("LO?K_").unwrap();
 
         // LOK
-        board.blacken(0, 0);
-        board.blacken(0, 1);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
 
         // Not allowed to change to gap, so this move is just ignored.
-        board.change_letter(0, 2, GAP_LETTER);
-        board.blacken(0, 3);
+        board.change_letter(0, 2, GAP_LETTER).unwrap();
+        board.blacken(0, 3).unwrap();
 
         // Exec LOK
-        board.blacken(0, 4);
+        board.blacken(0, 4).unwrap();
 
         assert_eq!(
             board.check_solution(),
@@ -2140,15 +2841,15 @@ mod tests {
         let mut board = Board::new("????").unwrap();
 
         // LOK
-        board.change_letter(0, 0, 'L');
-        board.change_letter(0, 1, 'O');
-        board.change_letter(0, 2, 'K');
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
+        board.change_letter(0, 0, 'L').unwrap();
+        board.change_letter(0, 1, 'O').unwrap();
+        board.change_letter(0, 2, 'K').unwrap();
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
 
         // Exec LOK
-        board.blacken(0, 3);
+        board.blacken(0, 3).unwrap();
 
         assert_eq!(board.check_solution(), SR::Correct);
     }
@@ -2158,15 +2859,15 @@ mod tests {
         let mut board = Board::new("????").unwrap();
 
         // LOK
-        board.change_letter(0, 0, 'L');
-        board.blacken(0, 0);
-        board.change_letter(0, 1, 'O');
-        board.blacken(0, 1);
-        board.change_letter(0, 2, 'K');
-        board.blacken(0, 2);
+        board.change_letter(0, 0, 'L').unwrap();
+        board.blacken(0, 0).unwrap();
+        board.change_letter(0, 1, 'O').unwrap();
+        board.blacken(0, 1).unwrap();
+        board.change_letter(0, 2, 'K').unwrap();
+        board.blacken(0, 2).unwrap();
 
         // Exec LOK
-        board.blacken(0, 3);
+        board.blacken(0, 3).unwrap();
 
         assert_eq!(board.check_solution(), SR::Correct);
     }
@@ -2176,19 +2877,19 @@ mod tests {
         let mut board = Board::new("BE_AQ").unwrap();
 
         // BE
-        board.blacken(0, 0);
-        board.blacken(0, 1);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
 
         // Exec BE
-        board.change_letter(0, 2, '?');
+        board.change_letter(0, 2, '?').unwrap();
 
         // TA
-        board.change_letter(0, 2, 'T');
-        board.blacken(0, 2);
-        board.blacken(0, 3);
+        board.change_letter(0, 2, 'T').unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
 
         // Exec TA
-        board.blacken(0, 4);
+        board.blacken(0, 4).unwrap();
 
         assert_eq!(board.check_solution(), SR::Correct);
     }
@@ -2198,13 +2899,13 @@ mod tests {
         let mut board = Board::new("LOQ_").unwrap();
 
         // LOK, but can't randomly change a regular letter
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.change_letter(0, 2, 'K');
-        board.blacken(0, 2);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.change_letter(0, 2, 'K').unwrap();
+        board.blacken(0, 2).unwrap();
 
         // Exec LOK
-        board.blacken(0, 3);
+        board.blacken(0, 3).unwrap();
 
         assert_eq!(
             board.check_solution(),
@@ -2217,13 +2918,13 @@ mod tests {
         let mut board = Board::new("LO_K").unwrap();
 
         // LOK, but can't randomly change a blank
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.change_letter(0, 2, 'K');
-        board.blacken(0, 2);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.change_letter(0, 2, 'K').unwrap();
+        board.blacken(0, 2).unwrap();
 
         // Exec LOK
-        board.blacken(0, 3);
+        board.blacken(0, 3).unwrap();
 
         assert_eq!(
             board.check_solution(),
@@ -2236,13 +2937,13 @@ mod tests {
         let mut board = Board::new("LO-K").unwrap();
 
         // LOK, but can't randomly change a gap
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.change_letter(0, 2, 'K');
-        board.blacken(0, 2);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.change_letter(0, 2, 'K').unwrap();
+        board.blacken(0, 2).unwrap();
 
         // Exec LOK
-        board.blacken(0, 3);
+        board.blacken(0, 3).unwrap();
 
         assert_eq!(
             board.check_solution(),
@@ -2255,21 +2956,21 @@ mod tests {
         let mut board = Board::new("?OK_AQ").unwrap();
 
         // LOK
-        board.change_letter(0, 0, 'L');
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
+        board.change_letter(0, 0, 'L').unwrap();
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
 
         // Exec LOK
-        board.blacken(0, 3);
+        board.blacken(0, 3).unwrap();
 
         // TA, but you can't change a blackened cell, even if it had a wildcard before
-        board.change_letter(0, 0, 'T');
-        board.blacken(0, 0);
-        board.blacken(0, 4);
+        board.change_letter(0, 0, 'T').unwrap();
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 4).unwrap();
 
         // Exec TA
-        board.blacken(0, 5);
+        board.blacken(0, 5).unwrap();
 
         assert_eq!(
             board.check_solution(),
@@ -2282,13 +2983,13 @@ mod tests {
         let mut board = Board::new("LOLO_").unwrap();
 
         // LOLO
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
 
         // Exec LOLO
-        board.blacken(0, 4);
+        board.blacken(0, 4).unwrap();
 
         assert_eq!(board.check_solution(), SR::Correct);
     }
@@ -2304,15 +3005,15 @@ mod tests {
         .unwrap();
 
         // LOLO
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
 
         // Exec LOLO
-        board.blacken(3, 0);
-        board.blacken(2, 1);
-        board.blacken(1, 2);
+        board.blacken(3, 0).unwrap();
+        board.blacken(2, 1).unwrap();
+        board.blacken(1, 2).unwrap();
 
         assert_eq!(board.check_solution(), SR::Correct);
     }
@@ -2328,14 +3029,14 @@ mod tests {
         .unwrap();
 
         // LOLO
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
 
         // Exec LOLO
-        board.blacken(3, 0);
-        board.blacken(1, 2);
+        board.blacken(3, 0).unwrap();
+        board.blacken(1, 2).unwrap();
 
         assert_eq!(board.check_solution(), SR::Correct);
     }
@@ -2345,10 +3046,10 @@ mod tests {
         let mut board = Board::new("LOLO").unwrap();
 
         // LOLO. No exec, because board is done.
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
 
         assert_eq!(board.check_solution(), SR::NotIdle);
     }
@@ -2364,15 +3065,15 @@ mod tests {
         .unwrap();
 
         // LOLO
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
 
         // Exec LOLO, but it only gets one cell because it's going to the upper-right.
-        board.blacken(3, 3);
-        board.blacken(2, 2);
-        board.blacken(1, 1);
+        board.blacken(3, 3).unwrap();
+        board.blacken(2, 2).unwrap();
+        board.blacken(1, 1).unwrap();
 
         assert_eq!(
             board.check_solution(),
@@ -2385,13 +3086,13 @@ mod tests {
         let mut board = Board::new("LOLO").unwrap();
 
         // LOLO
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
 
         // Exec LOLO, but it's not allowed to target a space that's already blackened
-        board.blacken(0, 0);
+        board.blacken(0, 0).unwrap();
 
         assert_eq!(
             board.check_solution(),
@@ -2409,28 +3110,28 @@ mod tests {
         .unwrap();
 
         // LO
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.mark_path(0, 3);
-        board.mark_path(1, 3);
-        board.mark_path(1, 0);
-        board.mark_path(0, 0);
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.mark_path(0, 3).unwrap();
+        board.mark_path(1, 3).unwrap();
+        board.mark_path(1, 0).unwrap();
+        board.mark_path(0, 0).unwrap();
 
         // LO
-        board.blacken(0, 1);
-        board.blacken(0, 2);
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
 
         // Exec LOLO, only one cell
-        board.blacken(1, 0);
+        board.blacken(1, 0).unwrap();
 
         // TA
-        board.blacken(2, 0);
-        board.blacken(2, 1);
+        board.blacken(2, 0).unwrap();
+        board.blacken(2, 1).unwrap();
 
         // Exec TA
-        board.blacken(0, 0);
-        board.blacken(0, 3);
-        board.blacken(1, 3);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 3).unwrap();
+        board.blacken(1, 3).unwrap();
 
         assert_eq!(board.check_solution(), SR::Correct);
     }
@@ -2446,14 +3147,14 @@ mod tests {
         .unwrap();
 
         // LOLO
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
 
         // Exec LOLO, but try to skip the top one
-        board.blacken(3, 0);
-        board.blacken(2, 1);
+        board.blacken(3, 0).unwrap();
+        board.blacken(2, 1).unwrap();
 
         assert_eq!(board.check_solution(), SR::NotIdle);
     }
@@ -2469,22 +3170,22 @@ mod tests {
         .unwrap();
 
         // LOLO
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
 
         // Exec LOLO, but try to skip the lowest one
-        board.blacken(2, 1);
-        board.blacken(1, 2);
+        board.blacken(2, 1).unwrap();
+        board.blacken(1, 2).unwrap();
 
         // LOK
-        board.blacken(1, 0);
-        board.blacken(1, 1);
-        board.blacken(1, 2);
+        board.blacken(1, 0).unwrap();
+        board.blacken(1, 1).unwrap();
+        board.blacken(1, 2).unwrap();
 
         // Exec LOK
-        board.blacken(3, 0);
+        board.blacken(3, 0).unwrap();
 
         assert_eq!(
             board.check_solution(),
@@ -2503,22 +3204,22 @@ mod tests {
         .unwrap();
 
         // LOLO
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
 
         // Exec LOLO, but try to skip the middle one
-        board.blacken(3, 0);
-        board.blacken(1, 2);
+        board.blacken(3, 0).unwrap();
+        board.blacken(1, 2).unwrap();
 
         // LOK
-        board.blacken(1, 0);
-        board.blacken(1, 1);
-        board.blacken(1, 2);
+        board.blacken(1, 0).unwrap();
+        board.blacken(1, 1).unwrap();
+        board.blacken(1, 2).unwrap();
 
         // Exec LOK
-        board.blacken(2, 1);
+        board.blacken(2, 1).unwrap();
 
         assert_eq!(
             board.check_solution(),
@@ -2537,22 +3238,22 @@ mod tests {
         .unwrap();
 
         // LOLO
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
 
         // Exec LOLO, but try to skip the top one
-        board.blacken(3, 0);
-        board.blacken(2, 1);
+        board.blacken(3, 0).unwrap();
+        board.blacken(2, 1).unwrap();
 
         // LOK
-        board.blacken(1, 0);
-        board.blacken(1, 1);
-        board.blacken(1, 2);
+        board.blacken(1, 0).unwrap();
+        board.blacken(1, 1).unwrap();
+        board.blacken(1, 2).unwrap();
 
         // Exec LOK
-        board.blacken(1, 2);
+        board.blacken(1, 2).unwrap();
 
         assert_eq!(
             board.check_solution(),
@@ -2569,15 +3270,15 @@ mod tests {
         .unwrap();
 
         // LOLO
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
 
         // Exec LOLO, but both cells are not on the same diagonal. So the first one finishes the LOLO and the second one
         // attempts to gather a new keyword.
-        board.blacken(1, 1);
-        board.blacken(1, 2);
+        board.blacken(1, 1).unwrap();
+        board.blacken(1, 2).unwrap();
 
         assert_eq!(
             board.check_solution(),
@@ -2595,15 +3296,15 @@ mod tests {
         .unwrap();
 
         // LOLO
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
 
         // Exec LOLO, but both cells are not on the same diagonal. So the first one finishes the LOLO and the second one
         // attempts to gather a new keyword.
-        board.blacken(1, 1);
-        board.blacken(2, 1);
+        board.blacken(1, 1).unwrap();
+        board.blacken(2, 1).unwrap();
 
         assert_eq!(
             board.check_solution(),
@@ -2621,15 +3322,15 @@ mod tests {
         .unwrap();
 
         // LOLO
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
 
         // Exec LOLO, but both cells are not on the same diagonal. So the first one finishes the LOLO and the second one
         // attempts to gather a new keyword.
-        board.blacken(2, 1);
-        board.blacken(1, 3);
+        board.blacken(2, 1).unwrap();
+        board.blacken(1, 3).unwrap();
 
         assert_eq!(
             board.check_solution(),
@@ -2647,19 +3348,67 @@ mod tests {
         .unwrap();
 
         // LOLO
-        board.blacken(0, 0);
-        board.blacken(0, 1);
-        board.blacken(0, 2);
-        board.blacken(0, 3);
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 3).unwrap();
 
         // Exec LOLO, but both cells are not on the same diagonal. So the first one finishes the LOLO and the second one
         // attempts to gather a new keyword.
-        board.blacken(1, 3);
-        board.blacken(2, 1);
+        board.blacken(1, 3).unwrap();
+        board.blacken(2, 1).unwrap();
 
         assert_eq!(
             board.check_solution(),
             SR::ErrorOnMove(5, ME::GatheringNonLetter)
         );
     }
+
+    /// A house-rule keyword that expects the player to blacken one cell with a vowel in it.
+    struct Ai;
+
+    impl keyword::Keyword for Ai {
+        fn letters(&self) -> &'static str {
+            "AI"
+        }
+
+        fn execute_blacken(
+            &self,
+            ctx: &mut ExecutionContext,
+            _progress: &ExecutionProgress,
+            rc: &RC,
+        ) -> Result<ExecutionProgress, MoveError> {
+            match ctx.grid()[rc].get_letter() {
+                Some('A' | 'E' | 'I' | 'O' | 'U') => {
+                    ctx.blacken_cell(rc);
+                    Ok(ExecutionProgress::Finished)
+                }
+                _ => Err(ME::CustomKeyword("AI needs a vowel".to_string())),
+            }
+        }
+    }
+
+    #[test]
+    fn house_rule_keyword() {
+        let registry = || {
+            let mut keywords = KeywordRegistry::standard();
+            keywords.register(Box::new(Ai)).unwrap();
+            keywords
+        };
+
+        let mut board = Board::with_keywords("AIE", registry()).unwrap();
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        assert_eq!(board.check_solution(), SR::Correct);
+
+        let mut board = Board::with_keywords("AIK", registry()).unwrap();
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        assert_eq!(
+            board.check_solution(),
+            SR::ErrorOnMove(2, ME::CustomKeyword("AI needs a vowel".to_string()))
+        );
+    }
 }
@@ -0,0 +1,180 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::RwLock;
+
+use wasm_bindgen::prelude::*;
+
+/// How much the rules engine logs. Each level includes everything from the levels before it.
+#[wasm_bindgen]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum LogLevel {
+    /// Nothing is logged.
+    Off = 0,
+
+    /// Each puzzle and move, and the reason a solution is incomplete.
+    Info = 1,
+
+    /// Every step of checking the rules, e.g. walking between cells for adjacency. This is very noisy, especially while
+    /// solving.
+    Trace = 2,
+}
+
+/// Somewhere for log messages to go.
+pub trait LogSink: Send + Sync {
+    /// Writes a single message that was logged at the given level.
+    fn log(&self, level: LogLevel, message: &str);
+}
+
+/// Writes log messages to the browser's console.
+#[cfg(all(feature = "console_log", target_arch = "wasm32"))]
+struct ConsoleSink;
+
+#[cfg(all(feature = "console_log", target_arch = "wasm32"))]
+impl LogSink for ConsoleSink {
+    fn log(&self, _level: LogLevel, message: &str) {
+        web_sys::console::log_1(&message.into());
+    }
+}
+
+/// Writes log messages to stderr, for native tools.
+pub struct StderrSink;
+
+impl LogSink for StderrSink {
+    fn log(&self, _level: LogLevel, message: &str) {
+        eprintln!("{}", message);
+    }
+}
+
+// In the browser, puzzles and moves go to the console unless told otherwise. Tracing every rule check would slow down
+// solving a lot, so the page has to ask for it with `set_log_level`. Elsewhere, nothing is logged by default.
+#[cfg(all(feature = "console_log", target_arch = "wasm32"))]
+const DEFAULT_LEVEL: LogLevel = LogLevel::Info;
+#[cfg(not(all(feature = "console_log", target_arch = "wasm32")))]
+const DEFAULT_LEVEL: LogLevel = LogLevel::Off;
+
+/// Gets where log messages go if no sink was set.
+#[cfg(all(feature = "console_log", target_arch = "wasm32"))]
+fn default_sink<'a>() -> Option<&'a dyn LogSink> {
+    Some(&ConsoleSink)
+}
+
+#[cfg(not(all(feature = "console_log", target_arch = "wasm32")))]
+fn default_sink<'a>() -> Option<&'a dyn LogSink> {
+    None
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(DEFAULT_LEVEL as u8);
+
+// The sink set by `set_log_sink`. If none was set, messages go to the default sink, if there is one.
+static SINK: RwLock<Option<Box<dyn LogSink>>> = RwLock::new(None);
+
+/// Sets how much the rules engine logs.
+#[wasm_bindgen]
+pub fn set_log_level(level: LogLevel) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+/// Gets how much the rules engine logs.
+#[wasm_bindgen]
+pub fn log_level() -> LogLevel {
+    match LEVEL.load(Ordering::Relaxed) {
+        0 => LogLevel::Off,
+        1 => LogLevel::Info,
+        _ => LogLevel::Trace,
+    }
+}
+
+/// Sends log messages to a different sink. `None` goes back to the default, which is the console in the browser and
+/// nowhere on other platforms. Note that nothing is logged at all while the level is `Off`; see `set_log_level`.
+pub fn set_log_sink(sink: Option<Box<dyn LogSink>>) {
+    *SINK.write().unwrap() = sink;
+}
+
+/// Returns whether messages at the given level would currently be logged. Checked before formatting a message, so that
+/// logging costs almost nothing while it is off.
+pub fn is_enabled(level: LogLevel) -> bool {
+    level != LogLevel::Off && level <= log_level()
+}
+
+/// Writes a message to the current sink. Use the `info!` and `trace!` macros instead of calling this directly.
+pub fn write(level: LogLevel, args: fmt::Arguments) {
+    let message = args.to_string();
+    if let Some(sink) = SINK.read().unwrap().as_deref().or_else(default_sink) {
+        sink.log(level, &message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Board;
+    use std::sync::{Arc, Mutex, MutexGuard};
+    use std::thread::{self, ThreadId};
+
+    // Held by every test that changes or depends on the global level and sink, so that they don't run at the same time.
+    static GLOBAL_STATE: Mutex<()> = Mutex::new(());
+
+    fn lock_global_state() -> MutexGuard<'static, ()> {
+        GLOBAL_STATE.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    // Other tests run in parallel and may log while a capturing sink is set, so it only keeps messages logged from the
+    // thread that created it.
+    struct CapturingSink {
+        thread: ThreadId,
+        messages: Arc<Mutex<Vec<(LogLevel, String)>>>,
+    }
+
+    impl LogSink for CapturingSink {
+        fn log(&self, level: LogLevel, message: &str) {
+            if thread::current().id() == self.thread {
+                self.messages
+                    .lock()
+                    .unwrap()
+                    .push((level, message.to_string()));
+            }
+        }
+    }
+
+    #[test]
+    fn off_by_default() {
+        let _lock = lock_global_state();
+        assert_eq!(DEFAULT_LEVEL, LogLevel::Off);
+        assert_eq!(log_level(), LogLevel::Off);
+        assert!(!is_enabled(LogLevel::Off));
+        assert!(!is_enabled(LogLevel::Info));
+    }
+
+    #[test]
+    fn levels_and_sink() {
+        let _lock = lock_global_state();
+        let messages = Arc::new(Mutex::new(vec![]));
+        set_log_sink(Some(Box::new(CapturingSink {
+            thread: thread::current().id(),
+            messages: messages.clone(),
+        })));
+
+        set_log_level(LogLevel::Info);
+        assert!(is_enabled(LogLevel::Info));
+        assert!(!is_enabled(LogLevel::Trace));
+        let mut board = Board::new("LOK\nLOK").unwrap();
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+
+        set_log_level(LogLevel::Trace);
+        board.blacken(0, 2).unwrap();
+
+        set_log_level(DEFAULT_LEVEL);
+        set_log_sink(None);
+
+        let messages = messages.lock().unwrap();
+        assert_eq!(
+            messages[0],
+            (LogLevel::Info, "puzzle:\nLOK\nLOK".to_string())
+        );
+        assert!(messages
+            .iter()
+            .any(|(level, message)| *level == LogLevel::Trace
+                && message.starts_with("Walk from RC(0, 1)")));
+    }
+}
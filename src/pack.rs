@@ -0,0 +1,551 @@
+//! A text format for collections of puzzles, with metadata about each puzzle. It's meant to be easy to edit by hand and
+//! to diff in source control:
+//!
+//! ```text
+//! # Lines starting with '#' are comments.
+//! title: First steps
+//! author: knutaf
+//! notes: Gather LOK, then blacken the last cell.
+//! keywords: LOK
+//! puzzle:
+//! LOK_
+//! solution:
+//! B 0 0
+//! B 0 1
+//! B 0 2
+//! B 0 3
+//!
+//! title: Second steps
+//! puzzle:
+//! ...
+//! ```
+//!
+//! Each puzzle starts with optional `title:`, `author:`, `notes:`, and `keywords:` lines, in any order. `notes:` may be
+//! given more than once for notes that span several lines, with an empty `notes:` for a blank line. Whitespace is
+//! trimmed from the other fields, but each line of notes is kept exactly as written after the `notes:` and one space, so
+//! that notes can be indented. Then `puzzle:` is followed by the rows of the puzzle, in the same format as `Board::new`,
+//! and optionally `solution:` followed by one move per line, in the same format as `Board::export_moves`. One or more
+//! blank lines end each puzzle. Since comments start with `#`, no row of a puzzle can start with one.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::grid::RC;
+use crate::keyword::KeywordRegistry;
+use crate::{Board, Move};
+
+/// A single puzzle from a pack, along with its metadata.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Puzzle {
+    title: Option<String>,
+    author: Option<String>,
+    notes: Option<String>,
+    keywords: Vec<String>,
+
+    // The rows of the puzzle, in the format that `Board::new` takes.
+    grid: String,
+
+    solution: Option<Vec<Move>>,
+}
+
+impl Puzzle {
+    /// Gets the title of the puzzle, if it has one.
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Gets who made the puzzle, if known.
+    pub fn author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+
+    /// Gets any notes about the puzzle, e.g. for the player or for other puzzle authors. Notes given on several lines are
+    /// joined with newlines.
+    pub fn notes(&self) -> Option<&str> {
+        self.notes.as_deref()
+    }
+
+    /// Gets the keywords that the puzzle is meant to be solved with. Empty if none were given.
+    pub fn keywords(&self) -> &[String] {
+        &self.keywords
+    }
+
+    /// Gets the rows of the puzzle, in the format that `Board::new` takes.
+    pub fn grid(&self) -> &str {
+        &self.grid
+    }
+
+    /// Gets the reference solution for the puzzle, if one was given.
+    pub fn solution(&self) -> Option<&[Move]> {
+        self.solution.as_deref()
+    }
+
+    /// Creates a board for playing the puzzle, with no moves made.
+    pub fn board(&self) -> Board {
+        Board::new(&self.grid).expect("puzzles in a pack are checked when the pack is parsed")
+    }
+
+    /// Creates a board for the puzzle with the reference solution applied, e.g. for checking that it's correct. Returns
+    /// `None` if there is no reference solution.
+    pub fn board_with_solution(&self) -> Option<Board> {
+        let solution = self.solution.as_ref()?;
+        let mut board = self.board();
+        for mv in solution.iter() {
+            board.make_move(mv);
+        }
+
+        Some(board)
+    }
+}
+
+/// A collection of puzzles read from a single file. See the module documentation for the format.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct PuzzlePack {
+    puzzles: Vec<Puzzle>,
+}
+
+impl PuzzlePack {
+    /// Gets the number of puzzles in the pack.
+    pub fn len(&self) -> usize {
+        self.puzzles.len()
+    }
+
+    /// Returns whether the pack has no puzzles.
+    pub fn is_empty(&self) -> bool {
+        self.puzzles.is_empty()
+    }
+
+    /// Gets a puzzle by its position in the pack, starting from 0.
+    pub fn get(&self, index: usize) -> Option<&Puzzle> {
+        self.puzzles.get(index)
+    }
+
+    /// Finds the first puzzle with the given title.
+    pub fn find(&self, title: &str) -> Option<&Puzzle> {
+        self.puzzles
+            .iter()
+            .find(|puzzle| puzzle.title() == Some(title))
+    }
+
+    /// Iterates over the puzzles in the order they appear in the pack.
+    pub fn iter(&self) -> std::slice::Iter<'_, Puzzle> {
+        self.puzzles.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a PuzzlePack {
+    type Item = &'a Puzzle;
+    type IntoIter = std::slice::Iter<'a, Puzzle>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// Which part of a puzzle the parser is in the middle of reading.
+#[derive(PartialEq)]
+enum Section {
+    // Reading the metadata lines before the puzzle itself.
+    Metadata,
+    Grid,
+    Solution,
+}
+
+/// Accumulates the lines of one puzzle while parsing a pack.
+struct PuzzleParser {
+    puzzle: Puzzle,
+    section: Section,
+
+    // The line that the puzzle started on, for reporting errors.
+    first_line_num: usize,
+
+    // The line that `puzzle:` was on, so that errors in the rows can be reported on the line they came from.
+    grid_line_num: usize,
+
+    // The lines of the solution and where they came from.
+    solution_lines: Vec<(usize, String)>,
+}
+
+impl PuzzleParser {
+    fn new(first_line_num: usize) -> PuzzleParser {
+        PuzzleParser {
+            puzzle: Puzzle {
+                title: None,
+                author: None,
+                notes: None,
+                keywords: vec![],
+                grid: String::new(),
+                solution: None,
+            },
+            section: Section::Metadata,
+            first_line_num,
+            grid_line_num: 0,
+            solution_lines: vec![],
+        }
+    }
+
+    fn set_once(field: &mut Option<String>, name: &str, value: &str) -> Result<(), String> {
+        if field.is_some() {
+            return Err(format!("The {} was already given!", name));
+        }
+
+        *field = Some(value.to_string());
+        Ok(())
+    }
+
+    fn parse_metadata(&mut self, line: &str) -> Result<(), String> {
+        let (key, raw_value) = match line.split_once(':') {
+            Some((key, value)) => (key.trim(), value),
+            None => {
+                return Err(format!(
+                    "Expected a field like \"title:\", but got \"{}\"!",
+                    line.trim()
+                ))
+            }
+        };
+        let value = raw_value.trim();
+
+        match key {
+            "title" => PuzzleParser::set_once(&mut self.puzzle.title, "title", value),
+            "author" => PuzzleParser::set_once(&mut self.puzzle.author, "author", value),
+            "notes" => {
+                // Every `notes:` line is a line of the notes, even a blank one.
+                let value = raw_value.strip_prefix(' ').unwrap_or(raw_value);
+                match &mut self.puzzle.notes {
+                    Some(notes) => {
+                        notes.push('\n');
+                        notes.push_str(value);
+                    }
+                    None => self.puzzle.notes = Some(value.to_string()),
+                }
+                Ok(())
+            }
+            "keywords" => {
+                let registry = KeywordRegistry::standard();
+                for keyword in value
+                    .split(|ch: char| ch == ',' || ch.is_whitespace())
+                    .filter(|keyword| !keyword.is_empty())
+                {
+                    if registry.find(keyword).is_none() {
+                        return Err(format!("{} is not a known keyword!", keyword));
+                    }
+
+                    self.puzzle.keywords.push(keyword.to_string());
+                }
+                Ok(())
+            }
+            "puzzle" if value.is_empty() => {
+                self.section = Section::Grid;
+                Ok(())
+            }
+            _ => Err(format!("Unknown field \"{}\"!", key)),
+        }
+    }
+
+    /// Handles one line of the puzzle. Comments and blank lines are handled by the caller.
+    fn parse_line(&mut self, line_num: usize, line: &str) -> Result<(), String> {
+        match self.section {
+            Section::Metadata => {
+                self.parse_metadata(line)?;
+                self.grid_line_num = line_num;
+            }
+            Section::Grid if line.trim() == "solution:" => self.section = Section::Solution,
+            Section::Grid => {
+                if !self.puzzle.grid.is_empty() {
+                    self.puzzle.grid.push('\n');
+                }
+                self.puzzle.grid.push_str(line);
+            }
+            Section::Solution => self
+                .solution_lines
+                .push((line_num, line.trim().to_string())),
+        }
+
+        Ok(())
+    }
+
+    /// Checks the puzzle and its solution now that all of its lines have been read.
+    fn finish(mut self) -> Result<Puzzle, String> {
+        if self.section == Section::Metadata {
+            return Err(format!(
+                "Line {}: The puzzle starting here has no \"puzzle:\" line!",
+                self.first_line_num
+            ));
+        }
+
+        if self.puzzle.grid.is_empty() {
+            return Err(format!("Line {}: The puzzle is empty!", self.grid_line_num));
+        }
+
+        // Errors in the rows say which row they're in, which is relative to the `puzzle:` line.
+        let board = Board::new(&self.puzzle.grid).map_err(|err| {
+            format!(
+                "Line {}: In the puzzle starting on the next line: {}",
+                self.grid_line_num, err
+            )
+        })?;
+
+        if self.section == Section::Solution {
+            let mut solution = vec![];
+            for (line_num, line) in self.solution_lines.iter() {
+                let mv: Move = line
+                    .parse()
+                    .map_err(|err| format!("Line {}: {}", line_num, err))?;
+
+                let RC(row, col) = mv.get_rc();
+                board
+                    .check_bounds(*row, *col)
+                    .map_err(|err| format!("Line {}: {}", line_num, err))?;
+
+                solution.push(mv);
+            }
+
+            self.puzzle.solution = Some(solution);
+        }
+
+        Ok(self.puzzle)
+    }
+}
+
+impl FromStr for PuzzlePack {
+    type Err = String;
+
+    /// Parses a pack of puzzles. Every puzzle is checked to be a valid board, and every move in a reference solution is
+    /// checked to be in bounds, but the solutions aren't checked against the rules.
+    fn from_str(text: &str) -> Result<PuzzlePack, String> {
+        let mut puzzles = vec![];
+        let mut parser: Option<PuzzleParser> = None;
+
+        for (line_num, line) in text.lines().enumerate() {
+            let line_num = line_num + 1;
+            if line.trim_start().starts_with('#') {
+                continue;
+            }
+
+            // A blank line ends the puzzle, but only once the puzzle itself has started, so that metadata can be spaced
+            // out a bit.
+            if line.trim().is_empty() {
+                match &parser {
+                    Some(current) if current.section != Section::Metadata => {
+                        puzzles.push(parser.take().unwrap().finish()?);
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+
+            parser
+                .get_or_insert_with(|| PuzzleParser::new(line_num))
+                .parse_line(line_num, line)
+                .map_err(|err| format!("Line {}: {}", line_num, err))?;
+        }
+
+        if let Some(parser) = parser {
+            puzzles.push(parser.finish()?);
+        }
+
+        Ok(PuzzlePack { puzzles })
+    }
+}
+
+impl fmt::Display for Puzzle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(title) = &self.title {
+            writeln!(f, "title: {}", title)?;
+        }
+
+        if let Some(author) = &self.author {
+            writeln!(f, "author: {}", author)?;
+        }
+
+        if let Some(notes) = &self.notes {
+            for line in notes.split('\n') {
+                if line.is_empty() {
+                    writeln!(f, "notes:")?;
+                } else {
+                    writeln!(f, "notes: {}", line)?;
+                }
+            }
+        }
+
+        if !self.keywords.is_empty() {
+            writeln!(f, "keywords: {}", self.keywords.join(" "))?;
+        }
+
+        writeln!(f, "puzzle:")?;
+        writeln!(f, "{}", self.grid)?;
+
+        if let Some(solution) = &self.solution {
+            writeln!(f, "solution:")?;
+            for mv in solution.iter() {
+                writeln!(f, "{}", mv)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes the pack in the same format that it's parsed from, without any of the original comments.
+impl fmt::Display for PuzzlePack {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, puzzle) in self.puzzles.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+
+            write!(f, "{}", puzzle)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SolutionResult;
+
+    const PACK: &str = "\
+# A pack for testing.
+title: First steps
+author: knutaf
+notes: Gather LOK,
+notes: then blacken the last cell.
+keywords: LOK
+puzzle:
+LOK_
+solution:
+B 0 0
+B 0 1
+B 0 2
+B 0 3
+
+
+# No metadata at all.
+puzzle:
+LOK_
+LOK_
+
+title: Conductors
+keywords: TLAK, TA
+puzzle:
+TXLX
+-K--
+";
+
+    #[test]
+    fn parse_pack() {
+        let pack: PuzzlePack = PACK.parse().unwrap();
+        assert_eq!(pack.len(), 3);
+
+        let first = pack.get(0).unwrap();
+        assert_eq!(first.title(), Some("First steps"));
+        assert_eq!(first.author(), Some("knutaf"));
+        assert_eq!(
+            first.notes(),
+            Some("Gather LOK,\nthen blacken the last cell.")
+        );
+        assert_eq!(first.keywords(), ["LOK".to_string()]);
+        assert_eq!(first.grid(), "LOK_");
+        assert_eq!(first.solution().unwrap().len(), 4);
+
+        let second = pack.get(1).unwrap();
+        assert_eq!(second.title(), None);
+        assert_eq!(second.notes(), None);
+        assert!(second.keywords().is_empty());
+        assert_eq!(second.grid(), "LOK_\nLOK_");
+        assert_eq!(second.solution(), None);
+
+        assert_eq!(
+            pack.find("Conductors").unwrap().keywords(),
+            ["TLAK".to_string(), "TA".to_string()]
+        );
+        assert_eq!(pack.find("Missing"), None);
+        assert_eq!(pack.get(3), None);
+    }
+
+    #[test]
+    fn reference_solution_solves_board() {
+        let pack: PuzzlePack = PACK.parse().unwrap();
+        let puzzle = pack.iter().next().unwrap();
+
+        let board = puzzle.board_with_solution().unwrap();
+        assert_eq!(board.check_solution(), SolutionResult::Correct);
+        assert_eq!(board.move_count(), 4);
+        assert!(pack.get(1).unwrap().board_with_solution().is_none());
+    }
+
+    #[test]
+    fn round_trip() {
+        let pack: PuzzlePack = PACK.parse().unwrap();
+        let text = pack.to_string();
+        assert!(text.starts_with("title: First steps\n"));
+        assert_eq!(text.parse::<PuzzlePack>().unwrap(), pack);
+    }
+
+    #[test]
+    fn round_trip_blank_notes() {
+        for notes in ["", "\nthen blacken it", "Gather LOK,\n\nthen blacken it\n"] {
+            let mut lines: Vec<String> = notes
+                .split('\n')
+                .map(|line| format!("notes: {}", line))
+                .collect();
+            lines.push("puzzle:\nLOK_".to_string());
+
+            let pack: PuzzlePack = lines.join("\n").parse().unwrap();
+            assert_eq!(pack.get(0).unwrap().notes(), Some(notes));
+            assert_eq!(pack.to_string().parse::<PuzzlePack>().unwrap(), pack);
+        }
+    }
+
+    #[test]
+    fn notes_keep_whitespace() {
+        let pack: PuzzlePack =
+            "  notes:Gather LOK,  \nnotes:   then blacken it\nnotes: \t\npuzzle:\nLOK_"
+                .parse()
+                .unwrap();
+        assert_eq!(
+            pack.get(0).unwrap().notes(),
+            Some("Gather LOK,  \n  then blacken it\n\t")
+        );
+        assert_eq!(pack.to_string().parse::<PuzzlePack>().unwrap(), pack);
+    }
+
+    #[test]
+    fn empty_pack() {
+        let pack: PuzzlePack = "# Nothing here yet.\n\n".parse().unwrap();
+        assert!(pack.is_empty());
+        assert_eq!((&pack).into_iter().count(), 0);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let err = |text: &str| text.parse::<PuzzlePack>().err().unwrap();
+
+        assert_eq!(
+            err("title: A\ntitle: B\npuzzle:\nLOK_"),
+            "Line 2: The title was already given!"
+        );
+        assert_eq!(
+            err("colour: red\npuzzle:\nLOK_"),
+            "Line 1: Unknown field \"colour\"!"
+        );
+        assert_eq!(
+            err("keywords: LOK KOL\npuzzle:\nLOK_"),
+            "Line 1: KOL is not a known keyword!"
+        );
+        assert_eq!(
+            err("title: A\n\nauthor: B"),
+            "Line 1: The puzzle starting here has no \"puzzle:\" line!"
+        );
+        assert_eq!(err("puzzle:\n\n"), "Line 1: The puzzle is empty!");
+        assert!(err("puzzle:\nLOK_\nLO")
+            .starts_with("Line 1: In the puzzle starting on the next line: Row 1"));
+        assert_eq!(
+            err("puzzle:\nLOK_\nsolution:\nB 0 0\nB 0 9"),
+            "Line 5: Row 0 col 9 is outside of the 1x4 board!"
+        );
+        assert!(err("puzzle:\nLOK_\nsolution:\nQ 0 0").starts_with("Line 4: "));
+    }
+}
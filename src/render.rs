@@ -0,0 +1,196 @@
+use std::fmt;
+
+use wasm_bindgen::prelude::*;
+
+use crate::grid::RC;
+use crate::{Board, BoardCell, BoardGrid, BLANK_LETTER, GAP_LETTER};
+
+/// How to render a board as text with `Board::render_text`.
+///
+/// Each cell takes three characters: its letter in the middle, with brackets around it if it's blackened, or
+/// parentheses if it's marked for a path. In ASCII, that looks like ` L `, `[L]`, and `(X)`, with `_` for blanks and `-`
+/// for gaps, as in the puzzle itself.
+#[wasm_bindgen]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct TextStyle {
+    /// Uses Unicode symbols instead of plain ASCII, which are easier to tell apart at a glance but harder to type.
+    pub unicode: bool,
+
+    /// Labels each row and column with its number.
+    pub coordinates: bool,
+
+    /// Shows how many times each cell was blackened or marked for a path after the cell, leaving it out for cells that
+    /// weren't.
+    pub mark_counts: bool,
+}
+
+#[wasm_bindgen]
+impl TextStyle {
+    /// Creates the default style: ASCII, without coordinates or mark counts.
+    pub fn new() -> TextStyle {
+        TextStyle::default()
+    }
+}
+
+impl TextStyle {
+    /// Gets the three characters that show a cell.
+    fn cell(&self, cell: &BoardCell) -> [char; 3] {
+        let letter = match (cell.get_letter_or_blank(), self.unicode) {
+            (None, false) => GAP_LETTER,
+            (None, true) => '·',
+            (Some(BLANK_LETTER), true) => '□',
+            (Some(letter), _) => letter,
+        };
+
+        match (cell.is_blackened(), cell.is_marked_for_path(), self.unicode) {
+            (true, _, false) => ['[', letter, ']'],
+            (true, _, true) => ['▐', letter, '▌'],
+            (false, true, false) => ['(', letter, ')'],
+            (false, true, true) => ['‹', letter, '›'],
+            (false, false, _) => [' ', letter, ' '],
+        }
+    }
+}
+
+/// Renders a grid of cells as text with one line per row. See `TextStyle` for what it looks like.
+pub fn render_grid(grid: &BoardGrid, style: &TextStyle) -> String {
+    let count_width = if style.mark_counts {
+        grid.iter()
+            .map(|cell| cell.get_mark_count())
+            .max()
+            .filter(|count| *count > 0)
+            .map_or(0, |count| count.to_string().len())
+    } else {
+        0
+    };
+
+    let row_label_width = if style.coordinates {
+        (grid.height().max(1) - 1).to_string().len() + 1
+    } else {
+        0
+    };
+
+    let mut lines = vec![];
+
+    if style.coordinates {
+        let mut line = " ".repeat(row_label_width);
+        for col in 0..grid.width() {
+            line.push_str(&format!("{:^3}", col));
+            line.push_str(&" ".repeat(count_width));
+        }
+        lines.push(line);
+    }
+
+    for row in 0..grid.height() {
+        let mut line = String::new();
+        if style.coordinates {
+            line.push_str(&format!("{:>1$} ", row, row_label_width - 1));
+        }
+
+        for col in 0..grid.width() {
+            let cell = &grid[&RC(row, col)];
+            line.extend(style.cell(cell).iter());
+
+            if count_width > 0 {
+                match cell.get_mark_count() {
+                    0 => line.push_str(&" ".repeat(count_width)),
+                    count => line.push_str(&format!("{:<1$}", count, count_width)),
+                }
+            }
+        }
+
+        lines.push(line);
+    }
+
+    // Trailing spaces get lost when pasting into most places, so leave them out to begin with.
+    lines
+        .iter()
+        .map(|line| line.trim_end())
+        .collect::<Vec<&str>>()
+        .join("\n")
+}
+
+#[wasm_bindgen]
+impl Board {
+    /// Renders the board as the player currently sees it, as text with one line per row. See `TextStyle` for what it
+    /// looks like.
+    pub fn render_text(&self, style: &TextStyle) -> String {
+        render_grid(self.get_latest(), style)
+    }
+}
+
+/// Renders the board in the default `TextStyle`.
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.render_text(&TextStyle::default()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board() -> Board {
+        let mut board = Board::new("TXA-\nK__L").unwrap();
+        board.blacken(0, 0).unwrap();
+        board.mark_path(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(1, 1).unwrap();
+        board
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            board().to_string(),
+            concat!(
+                "[T](X)[A] -\n", //
+                " K [_] _  L",
+            )
+        );
+    }
+
+    #[test]
+    fn unicode() {
+        let style = TextStyle {
+            unicode: true,
+            ..TextStyle::default()
+        };
+        assert_eq!(
+            board().render_text(&style),
+            concat!(
+                "▐T▌‹X›▐A▌ ·\n", //
+                " K ▐□▌ □  L",
+            )
+        );
+    }
+
+    #[test]
+    fn coordinates_and_mark_counts() {
+        let mut board =
+            Board::new("LOK_\nLOK_\nLOK_\nLOK_\nLOK_\nLOK_\nLOK_\nLOK_\nLOK_\nLOK_\nLOK_").unwrap();
+        board.blacken(0, 0).unwrap();
+        board.undo();
+        for _ in 0..10 {
+            board.mark_path(10, 3).unwrap();
+        }
+
+        let style = TextStyle {
+            coordinates: true,
+            mark_counts: true,
+            ..TextStyle::default()
+        };
+        let text = board.render_text(&style);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 12);
+        assert_eq!(lines[0], "    0    1    2    3");
+        assert_eq!(lines[1], " 0  L    O    K    _");
+        assert_eq!(lines[11], "10  L    O    K   (_)10");
+    }
+
+    #[test]
+    fn empty_style_matches_display() {
+        let board = board();
+        assert_eq!(board.render_text(&TextStyle::new()), board.to_string());
+    }
+}
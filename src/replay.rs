@@ -0,0 +1,223 @@
+use wasm_bindgen::prelude::*;
+
+use crate::render::{self, TextStyle};
+use crate::svg;
+use crate::trace::{TraceState, TraceStep};
+use crate::utils::Json;
+use crate::{Board, Move};
+
+/// How to render the board in each frame of a replay.
+#[wasm_bindgen]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum FrameFormat {
+    /// Text, like `render_text` with mark counts, so that looping paths can be followed.
+    Text,
+
+    /// A standalone SVG image, like `render_svg`.
+    Svg,
+}
+
+/// The board after one move of a replay, along with what the player was doing at that point.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ReplayFrame {
+    /// How many moves had been made. The first frame shows the puzzle before any moves, at step 0.
+    pub step: usize,
+
+    /// The move that was just made, or `None` for the first frame.
+    pub mv: Option<Move>,
+
+    /// The state of the simulation after the move, or `None` if the move was illegal or came after an illegal move.
+    pub state: Option<TraceState>,
+
+    /// Describes what the player was doing, e.g. "Gathering LO" or "Executing TLAK".
+    pub caption: String,
+
+    /// The board, rendered in the requested format.
+    pub board: String,
+}
+
+impl From<&ReplayFrame> for Json {
+    fn from(frame: &ReplayFrame) -> Json {
+        Json::Object(vec![
+            ("step", frame.step.into()),
+            ("move", frame.mv.as_ref().map(Move::to_string).into()),
+            ("caption", frame.caption.as_str().into()),
+            ("board", frame.board.as_str().into()),
+        ])
+    }
+}
+
+/// Describes what the player was doing after a move that was checked.
+fn caption(step: &TraceStep) -> String {
+    if let Some(err) = &step.error {
+        return format!("Illegal move: {:?}", err);
+    }
+
+    match (&step.before, step.after.as_ref().unwrap()) {
+        (_, TraceState::Executing { keyword, .. }) => format!("Executing {}", keyword),
+        (_, TraceState::Gathering { keyword, .. }) if !keyword.is_empty() => {
+            format!("Gathering {}", keyword)
+        }
+        (TraceState::Executing { keyword, .. }, _) => format!("Finished executing {}", keyword),
+        _ => "Idle".to_string(),
+    }
+}
+
+#[wasm_bindgen]
+impl Board {
+    /// Replays the moves like `replay`, but returns the frames as a JSON array of objects with `step`, `move`,
+    /// `caption`, and `board` fields. Moves are written in the same format as `export_moves`.
+    pub fn replay_json(&self, format: FrameFormat) -> String {
+        Json::Array(self.replay(format).iter().map(Into::into).collect()).to_string()
+    }
+}
+
+impl Board {
+    /// Replays the moves that are currently applied, rendering the board once before any moves and once after each
+    /// move, for an animated walkthrough of the solution. Each frame is annotated with the keyword being gathered or
+    /// executed at that point, as in `trace_solution`.
+    pub fn replay(&self, format: FrameFormat) -> Vec<ReplayFrame> {
+        let trace = self.trace_solution();
+        let style = TextStyle {
+            mark_counts: true,
+            ..TextStyle::default()
+        };
+
+        let mut grid = self.grid.clone();
+        let render = |grid: &_| match format {
+            FrameFormat::Text => render::render_grid(grid, &style),
+            FrameFormat::Svg => svg::render_grid(grid),
+        };
+
+        let mut frames = vec![ReplayFrame {
+            step: 0,
+            mv: None,
+            state: None,
+            caption: "Start".to_string(),
+            board: render(&grid),
+        }];
+
+        for (index, step) in self.moves.iter().enumerate() {
+            grid[&step.change.rc] = step.change.after;
+
+            // The trace stops at the first illegal move, but the player may have kept going after it.
+            let (state, caption) = match trace.steps.get(index) {
+                Some(trace_step) => (trace_step.after.clone(), caption(trace_step)),
+                None => (None, "Not checked, after an illegal move".to_string()),
+            };
+
+            frames.push(ReplayFrame {
+                step: index + 1,
+                mv: Some(step.mv.clone()),
+                state,
+                caption,
+                board: render(&grid),
+            });
+        }
+
+        frames
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn captions(board: &Board) -> Vec<String> {
+        board
+            .replay(FrameFormat::Text)
+            .into_iter()
+            .map(|frame| frame.caption)
+            .collect()
+    }
+
+    #[test]
+    fn text_frames() {
+        let mut board = Board::new("LOK_").unwrap();
+        for col in 0..4 {
+            board.blacken(0, col).unwrap();
+        }
+
+        let frames = board.replay(FrameFormat::Text);
+        assert_eq!(frames.len(), 5);
+        assert_eq!(frames[0].mv, None);
+        assert_eq!(frames[0].board, " L  O  K  _");
+        assert_eq!(frames[2].step, 2);
+        assert_eq!(frames[2].mv, Some("B 0 1".parse().unwrap()));
+        assert_eq!(frames[2].board, "[L]1[O]1 K   _");
+        assert_eq!(frames[4].board, "[L]1[O]1[K]1[_]1");
+
+        assert_eq!(
+            captions(&board),
+            vec![
+                "Start",
+                "Gathering L",
+                "Gathering LO",
+                "Executing LOK",
+                "Finished executing LOK",
+            ]
+        );
+    }
+
+    #[test]
+    fn x_loop_captions() {
+        let mut board = Board::new("TXX\n-XX\n-AX").unwrap();
+        board.blacken(0, 0).unwrap();
+        for (row, col) in [(0, 2), (1, 2), (1, 1), (0, 1), (0, 2)].iter() {
+            board.mark_path(*row, *col).unwrap();
+        }
+        board.mark_path(2, 2).unwrap();
+        board.blacken(2, 1).unwrap();
+        board.blacken(0, 1).unwrap();
+
+        let captions = captions(&board);
+        assert_eq!(captions.len(), 10);
+        assert!(captions[1..8]
+            .iter()
+            .all(|caption| caption == "Gathering T"));
+        assert_eq!(captions[8], "Executing TA");
+        assert_eq!(captions[9], "Executing TA");
+
+        // The loop passes through the first X twice.
+        let frames = board.replay(FrameFormat::Text);
+        assert!(frames[6].board.starts_with("[T]1(X)1(X)2"));
+    }
+
+    #[test]
+    fn frames_after_illegal_move() {
+        let mut board = Board::new("LOK_").unwrap();
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 1).unwrap();
+
+        let frames = board.replay(FrameFormat::Svg);
+        assert!(frames.iter().all(|frame| frame.board.starts_with("<svg")));
+        assert_eq!(frames[2].state, None);
+        assert_eq!(
+            frames
+                .iter()
+                .map(|frame| frame.caption.as_str())
+                .collect::<Vec<&str>>(),
+            vec![
+                "Start",
+                "Gathering L",
+                "Illegal move: BlackenNotConnectedForKeyword",
+                "Not checked, after an illegal move",
+            ]
+        );
+    }
+
+    #[test]
+    fn replay_json() {
+        let mut board = Board::new("LO").unwrap();
+        board.blacken(0, 0).unwrap();
+
+        assert_eq!(
+            board.replay_json(FrameFormat::Text),
+            concat!(
+                r#"[{"step":0,"move":null,"caption":"Start","board":" L  O"},"#,
+                r#"{"step":1,"move":"B 0 0","caption":"Gathering L","board":"[L]1 O"}]"#
+            )
+        );
+    }
+}
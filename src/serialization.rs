@@ -0,0 +1,219 @@
+//! Serde support for the types that need more than a derive, enabled by the `serde` feature.
+//!
+//! Moves serialize as text in the same format as `Board::export_moves`, e.g. `"B 0 1"`. A board serializes as the puzzle
+//! as it was given, the moves that are currently applied, and whether it's in strict mode. The simulation and the undo
+//! history aren't saved; they're rebuilt by replaying the moves.
+
+use serde::de::{self, Deserializer};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+
+use crate::keyword::KeywordRegistry;
+use crate::{Board, BoardCell, BoardGrid, Move, GAP_LETTER};
+
+impl Serialize for Move {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Move {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Move, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(de::Error::custom)
+    }
+}
+
+#[derive(Serialize)]
+struct BoardDataRef<'b> {
+    grid: &'b BoardGrid,
+    moves: Vec<&'b Move>,
+    is_strict: bool,
+}
+
+#[derive(Deserialize)]
+struct BoardData {
+    grid: BoardGrid,
+    moves: Vec<Move>,
+    is_strict: bool,
+}
+
+impl Serialize for Board {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        BoardDataRef {
+            grid: &self.grid,
+            moves: self.moves().collect(),
+            is_strict: self.is_strict,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Board {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Board, D::Error> {
+        let data = BoardData::deserialize(deserializer)?;
+
+        // The grid is the puzzle as it was given, before any moves, so every cell has to be exactly what `Board::new`
+        // would have made from its letter.
+        for (rc, cell) in data.grid.enumerate_row_col() {
+            let letter = cell.get_letter_or_blank().unwrap_or(GAP_LETTER);
+            if BoardCell::raw(letter).ok() != Some(*cell) {
+                return Err(de::Error::custom(format!(
+                    "Row {} col {} isn't a cell that a puzzle can start with!",
+                    rc.0, rc.1
+                )));
+            }
+        }
+
+        let mut board = Board::from_grid(data.grid, KeywordRegistry::standard());
+        for mv in data.moves.iter() {
+            let rc = mv.get_rc();
+            board.check_bounds(rc.0, rc.1).map_err(de::Error::custom)?;
+            board.make_move(mv);
+        }
+
+        board.is_strict = data.is_strict;
+        Ok(board)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::grid::{Grid, RC};
+    use crate::{Board, BoardCell, Move, MoveError, SolutionResult};
+
+    fn round_trip<T>(value: &T) -> T
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        serde_json::from_str(&serde_json::to_string(value).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn move_json() {
+        let mv = Move::ChangeLetter(RC(0, 3), 'T');
+        assert_eq!(serde_json::to_string(&mv).unwrap(), r#""C 0 3 T""#);
+        assert_eq!(round_trip(&mv), mv);
+        assert!(serde_json::from_str::<Move>(r#""Q 0 0""#).is_err());
+    }
+
+    #[test]
+    fn result_json() {
+        let result = SolutionResult::ErrorOnMove(2, MoveError::UnknownKeyword);
+        assert_eq!(
+            serde_json::to_string(&result).unwrap(),
+            r#"{"ErrorOnMove":[2,"UnknownKeyword"]}"#
+        );
+        assert_eq!(round_trip(&result), result);
+        assert_eq!(
+            serde_json::to_string(&SolutionResult::Correct).unwrap(),
+            r#""Correct""#
+        );
+    }
+
+    #[test]
+    fn cell_json() {
+        let cell = BoardCell::raw('?').unwrap();
+        assert_eq!(
+            serde_json::to_string(&cell).unwrap(),
+            r#"{"letter":"?","is_blackened":false,"is_marked_for_path":false,"was_ever_wildcard":true,"mark_count":0}"#
+        );
+        assert!(round_trip(&cell) == cell);
+    }
+
+    #[test]
+    fn grid_json() {
+        let mut grid = Grid::new(2, 1, &0);
+        grid[&RC(0, 1)] = 5;
+        assert_eq!(
+            serde_json::to_string(&grid).unwrap(),
+            r#"{"width":2,"height":1,"cells":[0,5]}"#
+        );
+
+        let grid: Grid<i32> = round_trip(&grid);
+        assert_eq!((grid.width(), grid.height()), (2, 1));
+        assert_eq!(grid[&RC(0, 1)], 5);
+
+        assert!(
+            serde_json::from_str::<Grid<i32>>(r#"{"width":2,"height":2,"cells":[0,5]}"#).is_err()
+        );
+        assert!(serde_json::from_str::<Grid<i32>>(r#"{"width":0,"height":2,"cells":[]}"#).is_err());
+        assert!(serde_json::from_str::<Grid<i32>>(&format!(
+            r#"{{"width":{},"height":2,"cells":[]}}"#,
+            usize::MAX
+        ))
+        .is_err());
+        assert!(serde_json::from_str::<Grid<i32>>(r#"{"width":2,"height":0,"cells":[]}"#).is_err());
+        assert!(serde_json::from_str::<Grid<i32>>(r#"{"width":0,"height":0,"cells":[]}"#).is_ok());
+
+        let mut grid = Grid::new(1, 2, &0);
+        grid.remove_col(0);
+        let grid: Grid<i32> = round_trip(&grid);
+        assert_eq!((grid.width(), grid.height()), (0, 0));
+    }
+
+    #[test]
+    fn board_round_trip() {
+        let mut board = Board::new("LOK_\n?-TA").unwrap();
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.change_letter(1, 0, 'X').unwrap();
+        board.set_strict(true);
+
+        let restored: Board = round_trip(&board);
+        assert_eq!(restored.export_moves(), board.export_moves());
+        assert_eq!(restored.check_solution(), board.check_solution());
+        assert!(restored.is_strict);
+        for row in 0..2 {
+            for col in 0..4 {
+                assert!(restored.get(row, col).unwrap() == board.get(row, col).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn board_with_illegal_moves() {
+        let mut board = Board::new("LOK_").unwrap();
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 3).unwrap();
+
+        let restored: Board = round_trip(&board);
+        assert_eq!(restored.check_solution(), board.check_solution());
+        assert_eq!(restored.move_count(), 2);
+    }
+
+    #[test]
+    fn board_with_impossible_cells() {
+        let json = serde_json::to_string(&Board::new("LOK_").unwrap()).unwrap();
+        assert!(serde_json::from_str::<Board>(&json).is_ok());
+
+        // Each of these changes the first cell, the L.
+        for (from, to) in [
+            (r#""letter":"L""#, r#""letter":"l""#),
+            (r#""letter":"L""#, r#""letter":"-""#),
+            (r#""letter":"L""#, r#""letter":"\u00d6""#),
+            (r#""is_blackened":false"#, r#""is_blackened":true"#),
+            (
+                r#""is_marked_for_path":false"#,
+                r#""is_marked_for_path":true"#,
+            ),
+            (
+                r#""was_ever_wildcard":false"#,
+                r#""was_ever_wildcard":true"#,
+            ),
+            (r#""mark_count":0"#, r#""mark_count":1"#),
+        ] {
+            assert!(json.contains(from));
+            let bad = json.replacen(from, to, 1);
+            assert!(serde_json::from_str::<Board>(&bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn board_out_of_bounds() {
+        let json = serde_json::to_string(&Board::new("LOK_").unwrap())
+            .unwrap()
+            .replace(r#""moves":[]"#, r#""moves":["B 3 0"]"#);
+        assert!(serde_json::from_str::<Board>(&json).is_err());
+    }
+}
@@ -0,0 +1,386 @@
+//! Compact codes for sharing a puzzle, and optionally the moves made on it, in a URL.
+//!
+//! A code is base64url, without padding, of the following bytes:
+//!
+//! - The format version, currently 1.
+//! - Flags. Bit 0 is set if there are moves.
+//! - The length of the puzzle text, as a varint.
+//! - The puzzle text, run-length encoded. Since puzzles are ASCII, bytes below 0x80 are literal, and a byte `0x80 | n`
+//!   followed by another byte repeats that byte `n + 3` times. Long runs of gaps and blanks are common in big puzzles.
+//! - If there are moves, the number of moves as a varint, then each move as a kind byte (0 to blacken, 1 to mark a
+//!   path, 2 to change a letter), the row and column as varints, and for letter changes, the letter.
+//!
+//! Varints are 7 bits per byte, least significant first, with the high bit set on every byte but the last.
+
+use wasm_bindgen::prelude::*;
+
+use crate::grid::RC;
+use crate::{Board, Move, GAP_LETTER};
+
+const VERSION: u8 = 1;
+const FLAG_MOVES: u8 = 1;
+
+// The shortest and longest runs that are run-length encoded. Shorter runs are just as short written out.
+const MIN_RUN: usize = 3;
+const MAX_RUN: usize = MIN_RUN + 0x7f;
+
+const MOVE_BLACKEN: u8 = 0;
+const MOVE_MARK_PATH: u8 = 1;
+const MOVE_CHANGE_LETTER: u8 = 2;
+
+const BASE64URL: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+fn base64url_encode(bytes: &[u8]) -> String {
+    let mut text = String::new();
+    for chunk in bytes.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, byte)| {
+            bits | (*byte as u32) << (16 - 8 * i)
+        });
+
+        // Every byte needs at least two characters, and each extra byte one more.
+        for i in 0..=chunk.len() {
+            text.push(BASE64URL[(bits >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+
+    text
+}
+
+fn base64url_decode(text: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = vec![];
+    let mut bits = 0u32;
+    let mut bit_count = 0;
+    for ch in text.chars() {
+        let value = BASE64URL
+            .iter()
+            .position(|c| *c as char == ch)
+            .ok_or_else(|| format!("'{}' can't be in a puzzle code!", ch))?;
+
+        bits = bits << 6 | value as u32;
+        bit_count += 6;
+        if bit_count >= 8 {
+            bit_count -= 8;
+            bytes.push((bits >> bit_count) as u8);
+            bits &= (1 << bit_count) - 1;
+        }
+    }
+
+    // Leftover bits only pad out the last character, so there can't be a whole character of them.
+    if bit_count >= 6 || bits != 0 {
+        return Err("The puzzle code is cut off!".to_string());
+    }
+
+    Ok(bytes)
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        bytes.push(value as u8 & 0x7f | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+/// Reads the bytes of a puzzle code in order, failing if they run out.
+struct Reader<'b> {
+    bytes: &'b [u8],
+    pos: usize,
+}
+
+impl<'b> Reader<'b> {
+    fn byte(&mut self) -> Result<u8, String> {
+        let byte = *self
+            .bytes
+            .get(self.pos)
+            .ok_or_else(|| "The puzzle code is cut off!".to_string())?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn varint(&mut self) -> Result<usize, String> {
+        let mut value = 0usize;
+        for shift in (0..usize::BITS).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as usize)
+                .checked_shl(shift)
+                .ok_or_else(|| "The puzzle code has a number that's too big!".to_string())?;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        Err("The puzzle code has a number that's too big!".to_string())
+    }
+}
+
+/// A puzzle and moves decoded from a code.
+#[wasm_bindgen]
+pub struct SharedPuzzle {
+    puzzle: String,
+    moves: Vec<Move>,
+}
+
+#[wasm_bindgen]
+impl SharedPuzzle {
+    /// Gets the puzzle as text that can be passed to `Board::new`, exactly as it was encoded.
+    pub fn get_puzzle(&self) -> String {
+        self.puzzle.clone()
+    }
+
+    /// Gets the moves as text that can be passed to `Board::import_moves`. Empty if there were none.
+    pub fn get_moves(&self) -> String {
+        self.moves
+            .iter()
+            .map(|mv| mv.to_string())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+impl SharedPuzzle {
+    /// Gets the puzzle as text that can be passed to `Board::new`, exactly as it was encoded.
+    pub fn puzzle(&self) -> &str {
+        &self.puzzle
+    }
+
+    /// Gets the moves that were encoded along with the puzzle.
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+}
+
+/// Encodes a puzzle and moves in the formats that `Board::new` and `Board::import_moves` take into a code. See
+/// `encode`.
+#[wasm_bindgen]
+pub fn encode_share_code(puzzle: &str, moves: &str) -> Result<String, String> {
+    let mut board = Board::new(puzzle)?;
+    board.import_moves(moves)?;
+    encode(puzzle, &board.moves().cloned().collect::<Vec<Move>>())
+}
+
+/// Decodes a code made by `encode_share_code`. See `decode`.
+#[wasm_bindgen]
+pub fn decode_share_code(code: &str) -> Result<SharedPuzzle, String> {
+    decode(code)
+}
+
+/// Encodes a puzzle, exactly as it would be passed to `Board::new`, and any moves made on it into a short code that is
+/// safe to use in a URL. Fails if the puzzle isn't valid or the moves are out of bounds.
+pub fn encode(puzzle: &str, moves: &[Move]) -> Result<String, String> {
+    let board = Board::new(puzzle)?;
+
+    let mut bytes = vec![VERSION, if moves.is_empty() { 0 } else { FLAG_MOVES }];
+
+    write_varint(&mut bytes, puzzle.len());
+    let puzzle = puzzle.as_bytes();
+    let mut i = 0;
+    while i < puzzle.len() {
+        let run = puzzle[i..]
+            .iter()
+            .take(MAX_RUN)
+            .take_while(|byte| **byte == puzzle[i])
+            .count();
+
+        if run >= MIN_RUN {
+            bytes.push(0x80 | (run - MIN_RUN) as u8);
+            bytes.push(puzzle[i]);
+            i += run;
+        } else {
+            bytes.push(puzzle[i]);
+            i += 1;
+        }
+    }
+
+    if !moves.is_empty() {
+        write_varint(&mut bytes, moves.len());
+        for mv in moves.iter() {
+            let RC(row, col) = mv.get_rc();
+            board.check_bounds(*row, *col)?;
+
+            bytes.push(match mv {
+                Move::Blacken(_) => MOVE_BLACKEN,
+                Move::MarkPath(_) => MOVE_MARK_PATH,
+                Move::ChangeLetter(_, _) => MOVE_CHANGE_LETTER,
+            });
+            write_varint(&mut bytes, *row);
+            write_varint(&mut bytes, *col);
+
+            if let Move::ChangeLetter(_, letter) = mv {
+                if !letter.is_ascii_graphic() || *letter == GAP_LETTER {
+                    return Err(format!("Cannot change a letter to \"{}\"", letter));
+                }
+                bytes.push(*letter as u8);
+            }
+        }
+    }
+
+    Ok(base64url_encode(&bytes))
+}
+
+/// Decodes a code made by `encode`. The puzzle and moves are checked the same way as when they were encoded, and a code
+/// that was cut off or has extra data at the end is reported as an error.
+pub fn decode(code: &str) -> Result<SharedPuzzle, String> {
+    let bytes = base64url_decode(code)?;
+    let mut reader = Reader {
+        bytes: &bytes,
+        pos: 0,
+    };
+
+    let version = reader.byte()?;
+    if version != VERSION {
+        return Err(format!(
+            "The puzzle code is from version {}, which isn't supported!",
+            version
+        ));
+    }
+
+    let flags = reader.byte()?;
+    if flags & !FLAG_MOVES != 0 {
+        return Err("The puzzle code has unknown flags!".to_string());
+    }
+
+    let len = reader.varint()?;
+    let mut puzzle = Vec::with_capacity(len.min(bytes.len() * MAX_RUN));
+    while puzzle.len() < len {
+        let byte = reader.byte()?;
+        if byte & 0x80 == 0 {
+            puzzle.push(byte);
+        } else {
+            let run = (byte & 0x7f) as usize + MIN_RUN;
+            let repeated = reader.byte()?;
+            if repeated & 0x80 != 0 {
+                return Err("The puzzle code has a non-ASCII character!".to_string());
+            }
+            puzzle.extend(std::iter::repeat_n(repeated, run));
+        }
+    }
+
+    if puzzle.len() != len {
+        return Err("The puzzle code's length doesn't match its contents!".to_string());
+    }
+
+    // Every byte is ASCII, so this can't fail.
+    let puzzle = String::from_utf8(puzzle).unwrap();
+    let board = Board::new(&puzzle)?;
+
+    let mut moves = vec![];
+    if flags & FLAG_MOVES != 0 {
+        let count = reader.varint()?;
+        for _ in 0..count {
+            let kind = reader.byte()?;
+            let rc = board.check_bounds(reader.varint()?, reader.varint()?)?;
+            moves.push(match kind {
+                MOVE_BLACKEN => Move::Blacken(rc),
+                MOVE_MARK_PATH => Move::MarkPath(rc),
+                MOVE_CHANGE_LETTER => {
+                    let letter = reader.byte()? as char;
+                    if !letter.is_ascii_graphic() || letter == GAP_LETTER {
+                        return Err(format!("Cannot change a letter to \"{}\"", letter));
+                    }
+                    Move::ChangeLetter(rc, letter)
+                }
+                _ => return Err(format!("The puzzle code has an unknown move {}!", kind)),
+            });
+        }
+    }
+
+    if reader.pos != bytes.len() {
+        return Err("The puzzle code has extra data at the end!".to_string());
+    }
+
+    Ok(SharedPuzzle { puzzle, moves })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64url_round_trip() {
+        for len in 0..10usize {
+            let bytes: Vec<u8> = (0..len).map(|i| (i * 97 + 200) as u8).collect();
+            let text = base64url_encode(&bytes);
+            assert_eq!(text.len(), (len * 4).div_ceil(3));
+            assert_eq!(base64url_decode(&text).unwrap(), bytes);
+        }
+
+        assert_eq!(base64url_encode(b"\xfb\xff"), "-_8");
+        assert!(base64url_decode("-_9").is_err());
+        assert!(base64url_decode("A").is_err());
+        assert!(base64url_decode("AA=").is_err());
+    }
+
+    #[test]
+    fn round_trip_puzzle() {
+        for puzzle in [
+            "LOK_",
+            "lo-_k-\nL_O_K_\nTLAK--\n",
+            "TXLX\n-K--\n-XAX\n----\nTAX_",
+            "----------------------------------------------------------------------------------------------------------------------------------------L",
+        ] {
+            let code = encode(puzzle, &[]).unwrap();
+            let shared = decode(&code).unwrap();
+            assert_eq!(shared.puzzle(), puzzle);
+            assert!(shared.moves().is_empty());
+        }
+    }
+
+    #[test]
+    fn round_trip_moves() {
+        let moves = vec![
+            Move::Blacken(RC(0, 0)),
+            Move::MarkPath(RC(2, 1)),
+            Move::ChangeLetter(RC(200, 3), 'T'),
+        ];
+        let mut puzzle = "LOK_\n".repeat(200);
+        puzzle.push_str("?--?");
+
+        let shared = decode(&encode(&puzzle, &moves).unwrap()).unwrap();
+        assert_eq!(shared.puzzle(), puzzle);
+        assert_eq!(shared.moves(), &moves[..]);
+    }
+
+    #[test]
+    fn runs_are_compact() {
+        let puzzle = format!("L{}\n{}K", "-".repeat(99), "_".repeat(99));
+        let code = encode(&puzzle, &[]).unwrap();
+        assert!(code.len() < 20);
+        assert_eq!(decode(&code).unwrap().puzzle(), puzzle);
+    }
+
+    #[test]
+    fn encode_errors() {
+        assert!(encode("LOK_\nLO", &[]).is_err());
+        assert!(encode("LOK_", &[Move::Blacken(RC(1, 0))]).is_err());
+    }
+
+    #[test]
+    fn decode_errors() {
+        let code = encode("LOK_", &[Move::Blacken(RC(0, 3))]).unwrap();
+        assert!(decode(&code[..code.len() - 2]).is_err());
+        assert!(decode(&format!("{}AA", code)).is_err());
+        assert!(decode("!!").is_err());
+        assert!(decode("").is_err());
+
+        // Version 2.
+        assert!(decode(&base64url_encode(&[2, 0, 1, b'L']))
+            .err()
+            .unwrap()
+            .contains("version 2"));
+
+        // A move outside the board.
+        assert!(decode(&base64url_encode(&[1, 1, 1, b'L', 1, 0, 0, 5])).is_err());
+
+        // A puzzle that Board::new rejects.
+        assert!(decode(&base64url_encode(&[1, 0, 4, b'L', b'\n', b'L', b'O'])).is_err());
+    }
+
+    #[test]
+    fn wasm_api() {
+        let code = encode_share_code("LOK_", "B 0 0\nB 0 1").unwrap();
+        let shared = decode_share_code(&code).unwrap();
+        assert_eq!(shared.get_puzzle(), "LOK_");
+        assert_eq!(shared.get_moves(), "B 0 0\nB 0 1");
+        assert!(encode_share_code("LOK_", "B 5 5").is_err());
+    }
+}
@@ -0,0 +1,953 @@
+use std::collections::HashSet;
+
+use wasm_bindgen::prelude::*;
+
+use crate::grid::{Direction, RC};
+use crate::keyword::{ExecutionProgress, KeywordId, KeywordRegistry};
+use crate::{Board, BoardState, Move, Simulation, BLANK_LETTER, CONDUCTOR_LETTER, WILDCARD_LETTER};
+
+/// The parts of a simulation that affect which moves are legal from here on out. Two simulations with the same key
+/// have exactly the same set of solutions from that point, so only one of them needs to be searched.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct SimulationKey {
+    // The letter, blackened state, and wildcard state of every cell. Path marks and mark counts are only cosmetic.
+    cells: Vec<(Option<char>, bool, bool)>,
+    state: StateKey,
+
+    // Limits which moves are tried next. See `min_blacken_rc`.
+    min_blacken_rc: Option<RC>,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum StateKey {
+    // While gathering a keyword, only the letters gathered so far, the set of cells that will be blackened when the
+    // keyword is complete, and the last two cells visited (which determine connectivity for the next move) matter.
+    Gathering(String, Vec<(usize, usize)>, Vec<RC>),
+    Executing(KeywordId, ExecutionProgress),
+}
+
+impl SimulationKey {
+    fn new(sim: &Simulation, last_move: Option<&Move>) -> SimulationKey {
+        let cells = sim
+            .grid
+            .iter()
+            .map(|cell| (cell.letter, cell.is_blackened, cell.was_ever_wildcard))
+            .collect();
+
+        let state = match &sim.state {
+            BoardState::GatheringKeyword(keyword, keyword_moves) => {
+                let mut blackened: Vec<(usize, usize)> = keyword_moves
+                    .iter()
+                    .filter_map(|mv| match mv {
+                        Move::Blacken(RC(row, col)) => Some((*row, *col)),
+                        _ => None,
+                    })
+                    .collect();
+                blackened.sort_unstable();
+                blackened.dedup();
+
+                let recent = keyword_moves
+                    .iter()
+                    .rev()
+                    .take(2)
+                    .map(|mv| mv.get_rc().clone())
+                    .collect();
+
+                StateKey::Gathering(keyword.clone(), blackened, recent)
+            }
+            BoardState::ExecutingKeyword(id, progress) => {
+                StateKey::Executing(*id, progress.clone())
+            }
+        };
+
+        SimulationKey {
+            cells,
+            state,
+            min_blacken_rc: min_blacken_rc(sim, last_move).cloned(),
+        }
+    }
+}
+
+/// Returns the letters that a wildcard or blank cell might usefully be changed to. Changing a letter to anything
+/// outside of the known keywords and special letters can't help build a keyword, so only those letters are considered.
+pub fn alphabet(keywords: &KeywordRegistry) -> Vec<char> {
+    let mut alphabet: Vec<char> = keywords
+        .letters()
+        .chain([CONDUCTOR_LETTER, WILDCARD_LETTER, BLANK_LETTER])
+        .collect();
+    alphabet.sort_unstable();
+    alphabet.dedup();
+    alphabet
+}
+
+/// The order that cells are blackened in while executing some keywords, like TLAK, TA, or LOLO, doesn't matter, so
+/// partway through one of those, only blackening cells later in reading order than the previous one is worth trying.
+/// Returns that previous cell, if there is one.
+fn min_blacken_rc<'m>(sim: &Simulation, last_move: Option<&'m Move>) -> Option<&'m RC> {
+    match (&sim.state, last_move) {
+        (BoardState::ExecutingKeyword(id, progress), Some(Move::Blacken(rc)))
+            if *progress != ExecutionProgress::Started
+                && !sim.keywords.get(*id).blacken_order_matters() =>
+        {
+            Some(rc)
+        }
+        _ => None,
+    }
+}
+
+/// Returns every move that could make a difference from the given simulation state. Some of them may still turn out to
+/// be illegal when applied. Both the search and `Board::legal_moves` start from these, so they agree on what counts as
+/// a move.
+///
+/// Moves that the rules allow but that can never help are left out: marking a path anywhere but on a conductor partway
+/// through a keyword, and changing letters that aren't blanks or wildcards, or to letters outside of `alphabet`.
+fn possible_moves(sim: &Simulation, alphabet: &[char]) -> Vec<Move> {
+    // Marking a path is only useful for redirecting through a conductor partway through a keyword.
+    let can_mark_path = match &sim.state {
+        BoardState::GatheringKeyword(_, keyword_moves) => !keyword_moves.is_empty(),
+        _ => false,
+    };
+
+    let mut moves = vec![];
+    for (rc, cell) in sim.grid.enumerate_row_col() {
+        if !cell.is_interactive() || cell.is_blackened() {
+            continue;
+        }
+
+        moves.push(Move::Blacken(rc.clone()));
+
+        if can_mark_path && cell.is_conductor() {
+            moves.push(Move::MarkPath(rc.clone()));
+        }
+
+        // Wildcards can be changed at any time, and some keywords, like BE, fill in blanks. Whether a change is
+        // actually allowed in the current state is left for the simulation to decide.
+        if cell.is_blank() || cell.was_ever_wildcard() {
+            for letter in alphabet.iter() {
+                if cell.letter != Some(*letter) {
+                    moves.push(Move::ChangeLetter(rc.clone(), *letter));
+                }
+            }
+        }
+    }
+
+    moves
+}
+
+/// Returns all moves that are worth trying from the given simulation state, given the move that led to it. Some of
+/// them may still turn out to be illegal when applied.
+pub fn candidate_moves(sim: &Simulation, alphabet: &[char], last_move: Option<&Move>) -> Vec<Move> {
+    let min_blacken_rc = min_blacken_rc(sim, last_move);
+
+    possible_moves(sim, alphabet)
+        .into_iter()
+        .filter(|mv| match (mv, min_blacken_rc) {
+            (Move::Blacken(rc), Some(min_rc)) => (rc.0, rc.1) > (min_rc.0, min_rc.1),
+            _ => true,
+        })
+        .collect()
+}
+
+/// Decides whether a search should leave out a move, given the simulation states before and after the move.
+type ExcludedMoves = dyn Fn(&Simulation, &Move, &Simulation) -> bool;
+
+/// A depth-first search over all the moves that a player could make, using the same rules as `check_solution`.
+pub struct Solver {
+    alphabet: Vec<char>,
+
+    // Every simulation state that has already been fully searched without finding a solution.
+    visited: HashSet<SimulationKey>,
+
+    // The moves that lead to the simulation state currently being searched.
+    pub moves: Vec<Move>,
+
+    // How many simulation states turned out to have no solution when searched.
+    pub dead_ends: usize,
+
+    // Moves that the search must never make. See `excluding`.
+    excluded: Option<Box<ExcludedMoves>>,
+
+    // How many simulation states the search may visit before giving up, if limited. See `with_max_steps`.
+    max_steps: Option<usize>,
+    steps: usize,
+}
+
+impl Solver {
+    pub fn new(keywords: &KeywordRegistry) -> Solver {
+        Solver {
+            alphabet: alphabet(keywords),
+            visited: HashSet::new(),
+            moves: vec![],
+            dead_ends: 0,
+            excluded: None,
+            max_steps: None,
+            steps: 0,
+        }
+    }
+
+    /// Creates a solver that gives up after visiting `max_steps` simulation states, so that searching a big or
+    /// unsolvable puzzle can't take forever. Once it gives up, `ran_out` is true, and nothing it returned since is
+    /// meaningful.
+    pub fn with_max_steps(keywords: &KeywordRegistry, max_steps: usize) -> Solver {
+        Solver {
+            max_steps: Some(max_steps),
+            ..Solver::new(keywords)
+        }
+    }
+
+    /// Returns whether the search gave up because it visited `max_steps` simulation states.
+    pub fn ran_out(&self) -> bool {
+        self.max_steps
+            .is_some_and(|max_steps| self.steps > max_steps)
+    }
+
+    /// Creates a solver that never makes a move that `excluded` returns true for, given the simulation states before
+    /// and after the move. Used for finding out whether a puzzle can be solved without some mechanic.
+    pub fn excluding(
+        keywords: &KeywordRegistry,
+        excluded: impl Fn(&Simulation, &Move, &Simulation) -> bool + 'static,
+    ) -> Solver {
+        Solver {
+            excluded: Some(Box::new(excluded)),
+            ..Solver::new(keywords)
+        }
+    }
+
+    /// Searches for a sequence of moves that solves the puzzle from the given simulation state. On success, `moves`
+    /// holds the solution.
+    pub fn search(&mut self, sim: &Simulation) -> bool {
+        if sim.is_idle() && sim.is_complete() {
+            return true;
+        }
+
+        self.steps += 1;
+        if self.ran_out() {
+            return false;
+        }
+
+        if !self
+            .visited
+            .insert(SimulationKey::new(sim, self.moves.last()))
+        {
+            return false;
+        }
+
+        for mv in candidate_moves(sim, &self.alphabet, self.moves.last()) {
+            let mut next_sim = sim.clone();
+            if next_sim.apply(&mv).is_err() {
+                continue;
+            }
+
+            if let Some(excluded) = &self.excluded {
+                if excluded(sim, &mv, &next_sim) {
+                    continue;
+                }
+            }
+
+            self.moves.push(mv);
+            if self.search(&next_sim) {
+                return true;
+            }
+
+            self.moves.pop();
+        }
+
+        self.dead_ends += 1;
+        false
+    }
+}
+
+/// Returns whether a sequence of moves solves the puzzle from the given simulation state.
+fn is_solution(start: &Simulation, moves: &[Move]) -> bool {
+    let mut sim = start.clone();
+    moves.iter().all(|mv| sim.apply(mv).is_ok()) && sim.is_idle() && sim.is_complete()
+}
+
+/// Removes letter changes that a solution doesn't actually need. The search tries changing a wildcard to every letter
+/// in turn, so the first solution it finds often changes the same wildcard several times before settling on a letter.
+pub fn without_needless_changes(start: &Simulation, mut moves: Vec<Move>) -> Vec<Move> {
+    for i in (0..moves.len()).rev() {
+        if let Move::ChangeLetter(_, _) = moves[i] {
+            let mut shorter = moves.clone();
+            shorter.remove(i);
+            if is_solution(start, &shorter) {
+                moves = shorter;
+            }
+        }
+    }
+
+    moves
+}
+
+/// One step of a solution, described in a way that ignores details that don't make a solution meaningfully different.
+#[derive(PartialEq, Eq, Hash)]
+enum SolutionStep {
+    // A keyword was gathered by visiting these cells, not counting path marks that don't change direction.
+    Gathered(Vec<Move>),
+
+    // A keyword was executed by blackening these cells, in reading order.
+    Blackened(Vec<RC>),
+
+    // BE was executed by filling in this cell with this letter.
+    Filled(RC, char),
+}
+
+/// Removes path marks from a keyword's moves where the path just passes straight through the marked cell, since the
+/// keyword could have been gathered the same way without them.
+pub fn without_straight_marks(keyword_moves: &[Move]) -> Vec<Move> {
+    keyword_moves
+        .iter()
+        .enumerate()
+        .filter(|(i, mv)| match mv {
+            Move::MarkPath(rc) if *i > 0 && *i + 1 < keyword_moves.len() => {
+                let prev_rc = keyword_moves[i - 1].get_rc();
+                let next_rc = keyword_moves[i + 1].get_rc();
+                Direction::between(prev_rc, rc) != Direction::between(rc, next_rc)
+            }
+            _ => true,
+        })
+        .map(|(_, mv)| mv.clone())
+        .collect()
+}
+
+/// Describes a solution as the keywords gathered and how each was executed. Two solutions with the same description
+/// are considered the same solution: they differ only in the order that cells were blackened during an execution,
+/// when wildcards were changed, or in path marks that don't redirect the path.
+fn describe_solution(start: &Simulation, moves: &[Move]) -> Vec<SolutionStep> {
+    let mut sim = start.clone();
+    let mut steps = vec![];
+    let mut executed = vec![];
+
+    for mv in moves.iter() {
+        let gathered_moves = match &sim.state {
+            BoardState::GatheringKeyword(_, keyword_moves) => Some(keyword_moves.clone()),
+            _ => None,
+        };
+        let state_before = sim.state.clone();
+
+        sim.apply(mv)
+            .expect("solutions should only contain legal moves");
+        let is_gathering = matches!(sim.state, BoardState::GatheringKeyword(_, _));
+
+        // Changing a letter only makes progress executing a keyword when the keyword is executed by filling in a cell,
+        // like BE. Otherwise, it's just changing a wildcard.
+        let was_filled = gathered_moves.is_none() && sim.state != state_before;
+
+        match (gathered_moves, mv) {
+            // The last letter of a keyword was just gathered.
+            (Some(mut keyword_moves), Move::Blacken(_)) if !is_gathering => {
+                keyword_moves.push(mv.clone());
+                steps.push(SolutionStep::Gathered(without_straight_marks(
+                    &keyword_moves,
+                )));
+            }
+            (None, Move::ChangeLetter(rc, letter)) if was_filled => {
+                steps.push(SolutionStep::Filled(rc.clone(), *letter));
+            }
+            (None, Move::Blacken(rc)) => executed.push(rc.clone()),
+            _ => {}
+        }
+
+        if is_gathering && !executed.is_empty() {
+            executed.sort_by_key(|rc| (rc.0, rc.1));
+            steps.push(SolutionStep::Blackened(std::mem::take(&mut executed)));
+        }
+    }
+
+    steps
+}
+
+/// A depth-first search that enumerates every distinct solution to a puzzle, up to a maximum number.
+struct SolutionEnumerator {
+    alphabet: Vec<char>,
+    max_solutions: usize,
+
+    // The simulation states along the path currently being searched, so that the search never loops back on itself.
+    on_path: HashSet<SimulationKey>,
+
+    // Simulation states that were fully searched without finding any solution.
+    dead_ends: HashSet<SimulationKey>,
+
+    // The moves that lead to the simulation state currently being searched.
+    moves: Vec<Move>,
+
+    // The descriptions of all the distinct solutions found so far, and one representative of each.
+    found: HashSet<Vec<SolutionStep>>,
+    representatives: Vec<Vec<Move>>,
+}
+
+impl SolutionEnumerator {
+    fn new(keywords: &KeywordRegistry, max_solutions: usize) -> SolutionEnumerator {
+        SolutionEnumerator {
+            alphabet: alphabet(keywords),
+            max_solutions,
+            on_path: HashSet::new(),
+            dead_ends: HashSet::new(),
+            moves: vec![],
+            found: HashSet::new(),
+            representatives: vec![],
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        self.representatives.len() >= self.max_solutions
+    }
+
+    /// Searches for all solutions reachable from the given simulation state. Returns whether this state is a dead end,
+    /// i.e. no solution can ever be reached from it, regardless of the path taken to get here.
+    fn search(&mut self, start: &Simulation, sim: &Simulation) -> bool {
+        if sim.is_idle() && sim.is_complete() {
+            let description = describe_solution(start, &self.moves);
+            if self.found.insert(description) {
+                self.representatives.push(self.moves.clone());
+            }
+
+            return false;
+        }
+
+        let key = SimulationKey::new(sim, self.moves.last());
+        if self.dead_ends.contains(&key) {
+            return true;
+        }
+
+        // Looping back to a state already on this path can't lead to any solution that isn't found without the loop.
+        // That doesn't mean this state is a dead end if reached some other way, though.
+        if self.on_path.contains(&key) {
+            return false;
+        }
+
+        self.on_path.insert(key.clone());
+
+        let mut is_dead_end = true;
+        for mv in candidate_moves(sim, &self.alphabet, self.moves.last()) {
+            if self.is_full() {
+                is_dead_end = false;
+                break;
+            }
+
+            let mut next_sim = sim.clone();
+            if next_sim.apply(&mv).is_err() {
+                continue;
+            }
+
+            self.moves.push(mv);
+            if !self.search(start, &next_sim) {
+                is_dead_end = false;
+            }
+            self.moves.pop();
+        }
+
+        self.on_path.remove(&key);
+        if is_dead_end {
+            self.dead_ends.insert(key);
+        }
+
+        is_dead_end
+    }
+}
+
+/// The distinct solutions found for a puzzle.
+pub struct SolutionCount {
+    /// One representative solution for each distinct solution that was found.
+    pub representatives: Vec<Vec<Move>>,
+
+    /// Whether the search stopped early because it found the maximum number of solutions requested, so there might be
+    /// more.
+    pub reached_max: bool,
+}
+
+impl SolutionCount {
+    /// The number of distinct solutions that were found.
+    pub fn count(&self) -> usize {
+        self.representatives.len()
+    }
+}
+
+/// Whether a puzzle can be solved, as far as a search could tell before it gave up.
+#[wasm_bindgen]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Solvability {
+    Solvable,
+    Unsolvable,
+
+    /// The search gave up before it could tell.
+    Unknown,
+}
+
+/// A move that the player could make next, for giving them a hint.
+#[wasm_bindgen]
+pub struct Hint {
+    mv: Move,
+}
+
+#[wasm_bindgen]
+impl Hint {
+    /// Gets the row of the cell to target.
+    pub fn get_row(&self) -> usize {
+        self.mv.get_rc().0
+    }
+
+    /// Gets the column of the cell to target.
+    pub fn get_col(&self) -> usize {
+        self.mv.get_rc().1
+    }
+
+    /// Gets the kind of move to make: "blacken", "markPath", or "changeLetter".
+    pub fn get_kind(&self) -> String {
+        match self.mv {
+            Move::Blacken(_) => "blacken",
+            Move::MarkPath(_) => "markPath",
+            Move::ChangeLetter(_, _) => "changeLetter",
+        }
+        .to_string()
+    }
+
+    /// Gets the letter to change the cell to, if the kind of move is "changeLetter".
+    pub fn get_letter(&self) -> Option<char> {
+        match self.mv {
+            Move::ChangeLetter(_, letter) => Some(letter),
+            _ => None,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Board {
+    /// Returns a move that the player could make next and still be able to solve the puzzle, if there is one. See
+    /// `hint`. Fails if the search for one visits more than `max_steps` states, so that the page doesn't hang on a big
+    /// puzzle.
+    pub fn get_hint(&self, max_steps: usize) -> Result<Option<Hint>, String> {
+        let mut solver = Solver::with_max_steps(&self.keywords, max_steps);
+        let hint = self.find_hint(&mut solver);
+        if solver.ran_out() {
+            return Err(format!("Couldn't find a hint within {} steps!", max_steps));
+        }
+
+        Ok(hint.map(|mv| Hint { mv }))
+    }
+
+    /// Returns whether the puzzle has any solution at all, without revealing what it is, giving up after visiting
+    /// `max_steps` states.
+    pub fn solvability(&self, max_steps: usize) -> Solvability {
+        let mut solver = Solver::with_max_steps(&self.keywords, max_steps);
+        if solver.search(&self.new_simulation()) {
+            Solvability::Solvable
+        } else if solver.ran_out() {
+            Solvability::Unknown
+        } else {
+            Solvability::Unsolvable
+        }
+    }
+
+    /// Counts the distinct solutions to the puzzle, stopping once `max_solutions` have been found.
+    pub fn count_solutions(&self, max_solutions: usize) -> usize {
+        self.enumerate_solutions(max_solutions).count()
+    }
+
+    /// Returns whether the puzzle has exactly one distinct solution.
+    pub fn has_unique_solution(&self) -> bool {
+        self.count_solutions(2) == 1
+    }
+}
+
+impl Board {
+    /// Returns whether the puzzle has any solution at all, without revealing what it is.
+    pub fn is_solvable(&self) -> bool {
+        self.solve().is_some()
+    }
+
+    /// Returns every move that could be made next without being illegal, given the moves made so far. If an earlier
+    /// move was already illegal, no move can fix that, so none are returned.
+    ///
+    /// These are the same moves that the solver tries, so moves that the rules allow but that can never help aren't
+    /// included: paths are only marked on conductors partway through a keyword, and letters are only changed in blanks
+    /// and wildcards, to ones that appear in a known keyword or have a special meaning.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        if self.first_error.is_some() {
+            return vec![];
+        }
+
+        possible_moves(&self.sim, &alphabet(&self.keywords))
+            .into_iter()
+            .filter(|mv| self.sim.clone().apply(mv).is_ok())
+            .collect()
+    }
+
+    /// Returns one of the `legal_moves` such that the puzzle can still be solved after making it. Returns `None` if the
+    /// puzzle is already solved, or if it can't be solved anymore without undoing some moves.
+    ///
+    /// This searches the whole puzzle, so it can take as long as `solve`. See `get_hint` for a version that gives up.
+    pub fn hint(&self) -> Option<Move> {
+        self.find_hint(&mut Solver::new(&self.keywords))
+    }
+
+    /// Finds a hint using the given solver. If the solver runs out of steps, the result is meaningless.
+    fn find_hint(&self, solver: &mut Solver) -> Option<Move> {
+        // A wildcard can usually be left as it is, and any change to one can be undone by changing it again, so letter
+        // changes are only hinted if nothing else works.
+        let (changes, mut others): (Vec<Move>, Vec<Move>) = self
+            .legal_moves()
+            .into_iter()
+            .partition(|mv| matches!(mv, Move::ChangeLetter(_, _)));
+
+        // Likewise, marking a path only matters if the path has to turn, so blackening is hinted first.
+        others.sort_by_key(|mv| matches!(mv, Move::MarkPath(_)));
+
+        // The solver is shared between these searches. Every state it visits in a search that fails is a dead end, so
+        // later searches can skip it.
+        for mv in others {
+            let mut next_sim = self.sim.clone();
+            next_sim.apply(&mv).unwrap();
+            solver.moves = vec![mv.clone()];
+            if solver.search(&next_sim) {
+                return Some(mv);
+            }
+
+            if solver.ran_out() {
+                return None;
+            }
+        }
+
+        // A letter has to change before anything else can happen. Any change keeps the puzzle solvable if it was
+        // before, so hint the one that a solution actually needs.
+        if changes.is_empty() {
+            return None;
+        }
+
+        solver.moves.clear();
+        if solver.search(&self.sim) {
+            let moves = std::mem::take(&mut solver.moves);
+            without_needless_changes(&self.sim, moves)
+                .into_iter()
+                .next()
+                .filter(|mv| changes.contains(mv))
+        } else {
+            None
+        }
+    }
+
+    /// Searches for a full sequence of moves that solves the puzzle from its initial state, ignoring any moves the
+    /// player has made so far. Returns `None` if the puzzle has no solution.
+    ///
+    /// Paths are only marked on conductors, and letters are only changed to ones that appear in a known keyword or
+    /// have a special meaning, since no other moves can contribute to a solution.
+    pub fn solve(&self) -> Option<Vec<Move>> {
+        let start = self.new_simulation();
+        let mut solver = Solver::new(&self.keywords);
+        if solver.search(&start) {
+            Some(without_needless_changes(&start, solver.moves))
+        } else {
+            None
+        }
+    }
+
+    /// Enumerates the distinct solutions to the puzzle from its initial state, stopping once `max_solutions` have been
+    /// found.
+    ///
+    /// Two solutions are the same if they gather the same keywords from the same cells and execute them on the same
+    /// cells. The order that cells are blackened while executing a keyword, when wildcards are changed, and path marks
+    /// that don't redirect the path don't matter.
+    pub fn enumerate_solutions(&self, max_solutions: usize) -> SolutionCount {
+        let mut enumerator = SolutionEnumerator::new(&self.keywords, max_solutions);
+        let start = self.new_simulation();
+        enumerator.search(&start, &start);
+
+        SolutionCount {
+            reached_max: enumerator.is_full(),
+            representatives: enumerator.representatives,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SolutionResult;
+
+    /// Solves the puzzle and checks that the solution found is accepted by the checker.
+    fn solve_and_check(puzzle: &str) -> Option<Vec<Move>> {
+        let mut board = Board::new(puzzle).unwrap();
+        let solution = board.solve()?;
+
+        // Every move in the solution should be legal, so none of them should be rejected in strict mode.
+        board.set_strict(true);
+        for mv in solution.iter() {
+            assert!(board.make_move(mv));
+        }
+
+        assert_eq!(board.check_solution(), SolutionResult::Correct);
+        Some(solution)
+    }
+
+    #[test]
+    fn solve_lok() {
+        let solution = solve_and_check("LOK_").unwrap();
+        assert_eq!(solution.len(), 4);
+    }
+
+    #[test]
+    fn solve_multiple_keywords() {
+        assert!(solve_and_check(
+            "LO-_K-\n\
+             L_O_K_\n\
+             TLAK--",
+        )
+        .is_some());
+    }
+
+    #[test]
+    fn solve_conductors() {
+        assert!(solve_and_check(
+            "TXLX\n\
+             -K--\n\
+             -XAX\n\
+             ----\n\
+             TAX_",
+        )
+        .is_some());
+    }
+
+    #[test]
+    fn solve_be() {
+        assert!(solve_and_check("BELO__").is_some());
+    }
+
+    #[test]
+    fn solve_wildcard() {
+        assert!(solve_and_check("LO?_").is_some());
+    }
+
+    #[test]
+    fn solve_skips_needless_changes() {
+        let solution = solve_and_check("LO??_").unwrap();
+        let changes = solution
+            .iter()
+            .filter(|mv| matches!(mv, Move::ChangeLetter(_, _)))
+            .count();
+        assert_eq!(changes, 2);
+    }
+
+    #[test]
+    fn solve_first_letter_wildcard() {
+        // The search tries changing a wildcard to a conductor after it was gathered as the first letter.
+        assert!(solve_and_check("?OK_").is_some());
+        assert!(Board::new("?OK_").unwrap().is_solvable());
+    }
+
+    #[test]
+    fn solve_lolo() {
+        assert!(solve_and_check(
+            "LOLO\n\
+             --_-\n\
+             -_--",
+        )
+        .is_some());
+    }
+
+    #[test]
+    fn unsolvable_cant_execute() {
+        let board = Board::new("LOK").unwrap();
+        assert_eq!(board.solve(), None);
+        assert!(!board.is_solvable());
+    }
+
+    #[test]
+    fn unsolvable_extra_space() {
+        assert_eq!(Board::new("LOK__").unwrap().solve(), None);
+    }
+
+    #[test]
+    fn unsolvable_out_of_order() {
+        assert_eq!(Board::new("LKO_").unwrap().solve(), None);
+    }
+
+    #[test]
+    fn solvable_ignores_player_moves() {
+        let mut board = Board::new("LOK_").unwrap();
+        board.blacken(0, 2).unwrap();
+        assert!(board.is_solvable());
+    }
+
+    #[test]
+    fn unique_lok() {
+        let board = Board::new("LOK_").unwrap();
+        assert!(board.has_unique_solution());
+
+        let solutions = board.enumerate_solutions(10);
+        assert_eq!(solutions.count(), 1);
+        assert!(!solutions.reached_max);
+    }
+
+    #[test]
+    fn unique_ignores_ta_order() {
+        let board = Board::new("TA___").unwrap();
+        assert_eq!(board.count_solutions(10), 1);
+    }
+
+    #[test]
+    fn unique_ignores_lolo_order() {
+        let board = Board::new(
+            "LOLO\n\
+             --_-\n\
+             -_--",
+        )
+        .unwrap();
+        assert_eq!(board.count_solutions(10), 1);
+    }
+
+    #[test]
+    fn unique_ignores_straight_path_marks() {
+        let board = Board::new("TXA").unwrap();
+        assert_eq!(board.count_solutions(10), 1);
+    }
+
+    #[test]
+    fn count_multiple_solutions() {
+        // Either LOK can be gathered first, and either one can blacken either blank.
+        let board = Board::new("LOKLOK__").unwrap();
+        assert!(!board.has_unique_solution());
+
+        let solutions = board.enumerate_solutions(10);
+        assert_eq!(solutions.count(), 4);
+        assert!(!solutions.reached_max);
+
+        for solution in solutions.representatives.iter() {
+            let mut board = Board::new("LOKLOK__").unwrap();
+            for mv in solution.iter() {
+                if let Move::Blacken(RC(row, col)) = mv {
+                    board.blacken(*row, *col).unwrap();
+                }
+            }
+            assert!(board.check());
+        }
+    }
+
+    #[test]
+    fn count_stops_at_max() {
+        let solutions = Board::new("LOKLOK__").unwrap().enumerate_solutions(2);
+        assert_eq!(solutions.count(), 2);
+        assert!(solutions.reached_max);
+    }
+
+    #[test]
+    fn count_unsolvable() {
+        let board = Board::new("LOK").unwrap();
+        assert_eq!(board.count_solutions(10), 0);
+        assert!(!board.has_unique_solution());
+    }
+
+    #[test]
+    fn legal_moves_at_start() {
+        let board = Board::new("LOK_").unwrap();
+        let legal_moves = board.legal_moves();
+        assert!(legal_moves.contains(&Move::Blacken(RC(0, 0))));
+        assert!(!legal_moves.contains(&Move::Blacken(RC(0, 1))));
+        assert!(!legal_moves.contains(&Move::Blacken(RC(0, 3))));
+        assert!(!legal_moves
+            .iter()
+            .any(|mv| matches!(mv, Move::ChangeLetter(_, _))));
+    }
+
+    #[test]
+    fn legal_moves_while_executing() {
+        let mut board = Board::new("BELO__").unwrap();
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+
+        // BE can only be executed by filling in a blank cell.
+        let legal_moves = board.legal_moves();
+        assert!(!legal_moves.is_empty());
+        assert!(legal_moves.iter().all(|mv| match mv {
+            Move::ChangeLetter(rc, _) => rc.1 >= 4,
+            _ => false,
+        }));
+    }
+
+    #[test]
+    fn legal_moves_after_error() {
+        let mut board = Board::new("LOK_").unwrap();
+        board.blacken(0, 3).unwrap();
+        assert!(board.legal_moves().is_empty());
+        assert_eq!(board.hint(), None);
+    }
+
+    #[test]
+    fn hints_lead_to_solution() {
+        let mut board = Board::new(
+            "LO-_K-\n\
+             L_O_K_\n\
+             TLAK--",
+        )
+        .unwrap();
+
+        while let Some(mv) = board.hint() {
+            assert!(board.legal_moves().contains(&mv));
+            assert!(board.make_move(&mv));
+        }
+
+        assert_eq!(board.check_solution(), SolutionResult::Correct);
+    }
+
+    #[test]
+    fn hint_skips_needless_changes() {
+        // The wildcard can be blackened as it is, but the search tries changing it before it gets to the LOK.
+        let board = Board::new("?LOK").unwrap();
+        assert_eq!(board.hint(), Some(Move::Blacken(RC(0, 1))));
+    }
+
+    #[test]
+    fn no_hint_when_stuck() {
+        let mut board = Board::new("LOKLOK__").unwrap();
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 4).unwrap();
+        assert!(!board.legal_moves().is_empty());
+        assert_eq!(board.hint(), None);
+    }
+
+    #[test]
+    fn get_hint() {
+        let hint = Board::new("LOK_").unwrap().get_hint(1000).unwrap().unwrap();
+        assert_eq!((hint.get_row(), hint.get_col()), (0, 0));
+        assert_eq!(hint.get_kind(), "blacken");
+        assert_eq!(hint.get_letter(), None);
+    }
+
+    #[test]
+    fn hint_changes_letter_when_needed() {
+        let board = Board::new("?OK_").unwrap();
+        assert_eq!(board.hint(), Some(Move::ChangeLetter(RC(0, 0), 'L')));
+    }
+
+    #[test]
+    fn legal_moves_only_mark_conductors() {
+        let mut board = Board::new("LXOK_").unwrap();
+        board.blacken(0, 0).unwrap();
+        let legal_moves = board.legal_moves();
+        assert!(legal_moves.contains(&Move::MarkPath(RC(0, 1))));
+        assert!(!legal_moves.contains(&Move::MarkPath(RC(0, 2))));
+    }
+
+    #[test]
+    fn search_gives_up() {
+        let board = Board::new(
+            "LOK_LOK_\n\
+             ___LOK__\n\
+             TLAKLOK_\n\
+             __LOK___",
+        )
+        .unwrap();
+        assert_eq!(board.solvability(5), Solvability::Unknown);
+        assert!(board.get_hint(5).is_err());
+
+        assert_eq!(
+            Board::new("LOK_").unwrap().solvability(1000),
+            Solvability::Solvable
+        );
+        assert_eq!(
+            Board::new("LOK").unwrap().solvability(1000),
+            Solvability::Unsolvable
+        );
+    }
+}
@@ -0,0 +1,244 @@
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+
+use crate::utils::fnv1a;
+use crate::Board;
+
+/// Somewhere to keep the player's progress on each puzzle, so that it survives reloading the page.
+pub trait ProgressStore {
+    /// Gets the value saved under the given key, if any.
+    fn load(&self, key: &str) -> Option<String>;
+
+    /// Saves a value under the given key, replacing whatever was there.
+    fn save(&self, key: &str, value: &str);
+
+    /// Removes the value saved under the given key, if any.
+    fn remove(&self, key: &str);
+}
+
+/// Keeps progress in the browser's `localStorage`. Failures, e.g. from the storage quota being full, are ignored, since
+/// losing progress is better than not being able to play at all.
+#[cfg(feature = "local_storage")]
+pub struct LocalStorage {
+    storage: web_sys::Storage,
+}
+
+#[cfg(feature = "local_storage")]
+impl LocalStorage {
+    /// Gets the page's `localStorage`, if it's available. It might not be, e.g. if the browser blocks it in private
+    /// browsing.
+    pub fn new() -> Option<LocalStorage> {
+        let storage = web_sys::window()?.local_storage().ok()??;
+        Some(LocalStorage { storage })
+    }
+}
+
+#[cfg(feature = "local_storage")]
+impl ProgressStore for LocalStorage {
+    fn load(&self, key: &str) -> Option<String> {
+        self.storage.get_item(key).ok()?
+    }
+
+    fn save(&self, key: &str, value: &str) {
+        let _ = self.storage.set_item(key, value);
+    }
+
+    fn remove(&self, key: &str) {
+        let _ = self.storage.remove_item(key);
+    }
+}
+
+/// Gets where progress is kept by default, which is `localStorage` in the browser and nowhere on other platforms.
+#[cfg(all(feature = "local_storage", target_arch = "wasm32"))]
+fn default_store() -> Option<Rc<dyn ProgressStore>> {
+    LocalStorage::new().map(|store| Rc::new(store) as Rc<dyn ProgressStore>)
+}
+
+#[cfg(not(all(feature = "local_storage", target_arch = "wasm32")))]
+fn default_store() -> Option<Rc<dyn ProgressStore>> {
+    None
+}
+
+/// Where a board saves its moves after every change.
+pub struct Autosave {
+    store: Rc<dyn ProgressStore>,
+    key: String,
+}
+
+impl Autosave {
+    /// Saves the moves currently applied to the board. Once there are no moves left, the save is removed instead of
+    /// leaving an empty one behind.
+    pub fn save(&self, board: &Board) {
+        if board.move_count() == 0 {
+            self.store.remove(&self.key);
+        } else {
+            self.store.save(&self.key, &board.export_moves());
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Board {
+    /// Constructs a new board like `new`, then restores the moves that were saved for the same puzzle, if any, and saves
+    /// the moves again after every change from then on. In the browser, progress is saved in `localStorage`. Elsewhere,
+    /// this is the same as `new`.
+    pub fn restore(contents: &str) -> Result<Board, String> {
+        let mut board = Board::new(contents)?;
+        if let Some(store) = default_store() {
+            board.restore_progress(store);
+        }
+
+        Ok(board)
+    }
+}
+
+impl Board {
+    /// Gets the key that progress on this puzzle is saved under. It is based only on the puzzle as it was given, so
+    /// the same puzzle always has the same key, no matter how it was entered, e.g. in upper or lower case.
+    pub fn progress_key(&self) -> String {
+        format!(
+            "lok-progress-{:016x}",
+            fnv1a(&Board::puzzle_text(&self.grid))
+        )
+    }
+
+    /// Restores the moves that were saved in the given store for this puzzle, replacing any moves already made, and
+    /// saves the moves there again after every change from then on. Saved moves that can't be read, e.g. from an older
+    /// version, are ignored.
+    pub fn restore_progress(&mut self, store: Rc<dyn ProgressStore>) {
+        let key = self.progress_key();
+        if let Some(saved) = store.load(&key) {
+            if let Err(err) = self.import_moves(&saved) {
+                info!("Ignoring saved progress {}: {}", key, err);
+            }
+        }
+
+        self.autosave = Some(Autosave { store, key });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+    use std::collections::HashMap;
+
+    #[derive(Default)]
+    struct MemoryStore {
+        values: RefCell<HashMap<String, String>>,
+        writes: Cell<usize>,
+    }
+
+    impl ProgressStore for MemoryStore {
+        fn load(&self, key: &str) -> Option<String> {
+            self.values.borrow().get(key).cloned()
+        }
+
+        fn save(&self, key: &str, value: &str) {
+            self.writes.set(self.writes.get() + 1);
+            self.values
+                .borrow_mut()
+                .insert(key.to_string(), value.to_string());
+        }
+
+        fn remove(&self, key: &str) {
+            self.writes.set(self.writes.get() + 1);
+            self.values.borrow_mut().remove(key);
+        }
+    }
+
+    #[test]
+    fn progress_key() {
+        let key = Board::new("LOK_\n-TA_").unwrap().progress_key();
+        assert!(key.starts_with("lok-progress-"));
+        assert_eq!(Board::new("lok_\n-ta_").unwrap().progress_key(), key);
+        assert_ne!(Board::new("LOK_\nTA_-").unwrap().progress_key(), key);
+    }
+
+    #[test]
+    fn saves_every_change() {
+        let store = Rc::new(MemoryStore::default());
+        let mut board = Board::new("LOK_").unwrap();
+        board.restore_progress(store.clone());
+        let key = board.progress_key();
+        assert_eq!(store.load(&key), None);
+
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+        assert_eq!(store.load(&key).unwrap(), "B 0 0\nB 0 1");
+
+        board.undo();
+        assert_eq!(store.load(&key).unwrap(), "B 0 0");
+
+        board.redo();
+        assert_eq!(store.load(&key).unwrap(), "B 0 0\nB 0 1");
+
+        board.undo();
+        board.undo();
+        assert_eq!(store.load(&key), None);
+    }
+
+    #[test]
+    fn saves_once_per_change() {
+        let store = Rc::new(MemoryStore::default());
+        let mut board = Board::new("LOK_").unwrap();
+        board.restore_progress(store.clone());
+        let key = board.progress_key();
+
+        board.import_moves("B 0 0\nB 0 1\nB 0 2").unwrap();
+        assert_eq!(store.writes.get(), 1);
+        assert_eq!(store.load(&key).unwrap(), "B 0 0\nB 0 1\nB 0 2");
+
+        board.undo();
+        board.undo();
+        board.blacken(0, 2).unwrap();
+        assert_eq!(store.writes.get(), 4);
+
+        board.switch_branch(1, 0);
+        assert_eq!(store.writes.get(), 5);
+        assert_eq!(store.load(&key).unwrap(), "B 0 0\nB 0 1\nB 0 2");
+    }
+
+    #[test]
+    fn restores_saved_moves() {
+        let store = Rc::new(MemoryStore::default());
+        let mut board = Board::new("LOK_").unwrap();
+        board.restore_progress(store.clone());
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+
+        // Progress on one puzzle doesn't show up in another.
+        let mut other = Board::new("LOK_\nLOK_").unwrap();
+        other.restore_progress(store.clone());
+        assert_eq!(other.move_count(), 0);
+
+        let mut restored = Board::new("LOK_").unwrap();
+        restored.restore_progress(store.clone());
+        assert_eq!(restored.export_moves(), "B 0 0\nB 0 1");
+        restored.blacken(0, 2).unwrap();
+        restored.blacken(0, 3).unwrap();
+        assert!(restored.check());
+    }
+
+    #[test]
+    fn ignores_unreadable_progress() {
+        let store = Rc::new(MemoryStore::default());
+        let mut board = Board::new("LOK_").unwrap();
+        store.save(&board.progress_key(), "B 0 0\nB 9 9");
+
+        board.restore_progress(store.clone());
+        assert_eq!(board.move_count(), 0);
+
+        // The unreadable progress is replaced by the next change.
+        board.blacken(0, 0).unwrap();
+        assert_eq!(store.load(&board.progress_key()).unwrap(), "B 0 0");
+    }
+
+    #[test]
+    fn restore_without_storage() {
+        let board = Board::restore("LOK_").unwrap();
+        assert_eq!(board.move_count(), 0);
+        assert!(Board::restore("LOK_\nLO").is_err());
+    }
+}
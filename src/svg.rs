@@ -0,0 +1,190 @@
+use std::fmt::Write;
+
+use wasm_bindgen::prelude::*;
+
+use crate::grid::RC;
+use crate::utils::xml_text;
+use crate::{Board, BoardCell, BoardGrid};
+
+// Sizes in pixels, matching how the page lays out the board's table with a medium font: cells are 2em square, with the
+// table's default spacing between them.
+const CELL_SIZE: usize = 32;
+const CELL_SPACING: usize = 2;
+const BORDER_WIDTH: usize = 2;
+
+// The same look as the classes with the same names in `www/index.html`, translated to SVG.
+const STYLE: &str = "\
+rect.normal_cell { fill: white; stroke: black; stroke-width: 2px; }
+rect.blackened { fill: black; }
+rect.pathmarked { stroke-dasharray: 2 2; }
+rect.blackened.pathmarked { stroke: white; }
+text { font-family: Consolas, monospace; font-size: 16px; text-anchor: middle; dominant-baseline: central; }
+text.blackened { fill: white; }
+text.mark_count { font-size: 13px; text-anchor: end; dominant-baseline: auto; }";
+
+/// Gets the CSS classes for a cell's parts, the same as the page gives the cell in the table.
+fn classes(base: &str, cell: &BoardCell) -> String {
+    let mut classes = String::from(base);
+    if cell.is_blackened() {
+        classes.push_str(" blackened");
+    }
+
+    if cell.is_marked_for_path() {
+        classes.push_str(" pathmarked");
+    }
+
+    classes
+}
+
+/// Renders a grid of cells as a standalone SVG image that looks like the board on the page.
+pub fn render_grid(grid: &BoardGrid) -> String {
+    let pitch = CELL_SIZE + CELL_SPACING;
+    let width = CELL_SPACING + grid.width() * pitch;
+    let height = CELL_SPACING + grid.height() * pitch;
+
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{0}" height="{1}" viewBox="0 0 {0} {1}">"#,
+        width, height
+    )
+    .unwrap();
+    writeln!(svg, "<style>\n{}\n</style>", STYLE).unwrap();
+    writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#).unwrap();
+
+    for row in 0..grid.height() {
+        for col in 0..grid.width() {
+            let cell = &grid[&RC(row, col)];
+
+            // Gaps are left empty, like on the page.
+            if !cell.is_interactive() {
+                continue;
+            }
+
+            let x = CELL_SPACING + col * pitch;
+            let y = CELL_SPACING + row * pitch;
+
+            // SVG draws borders centered on the edge of a shape, so the shape is inset to keep the border inside the
+            // cell.
+            writeln!(
+                svg,
+                r#"<rect class="{0}" x="{1}" y="{2}" width="{3}" height="{3}"/>"#,
+                classes("normal_cell", cell),
+                x + BORDER_WIDTH / 2,
+                y + BORDER_WIDTH / 2,
+                CELL_SIZE - BORDER_WIDTH
+            )
+            .unwrap();
+
+            let letter = cell.get_display();
+            if letter != ' ' {
+                writeln!(
+                    svg,
+                    r#"<text class="{}" x="{}" y="{}">{}</text>"#,
+                    classes("letter", cell),
+                    x + CELL_SIZE / 2,
+                    y + CELL_SIZE / 2,
+                    xml_text(&letter.to_string())
+                )
+                .unwrap();
+            }
+
+            // Like on the page, a count is only worth showing once a cell has been used more than once.
+            if cell.get_mark_count() > 1 {
+                writeln!(
+                    svg,
+                    r#"<text class="{}" x="{}" y="{}">{}</text>"#,
+                    classes("mark_count", cell),
+                    x + CELL_SIZE - BORDER_WIDTH - 1,
+                    y + BORDER_WIDTH + 11,
+                    cell.get_mark_count()
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[wasm_bindgen]
+impl Board {
+    /// Renders the board as the player saw it after the given number of moves, as a standalone SVG image that looks like
+    /// the board on the page. Passing `move_count()` renders the board as it is now.
+    pub fn render_svg(&self, step: usize) -> Result<String, String> {
+        let grid = self.get_at_step(step).ok_or_else(|| {
+            format!(
+                "Step {} is past the {} moves that were made!",
+                step,
+                self.move_count()
+            )
+        })?;
+
+        Ok(render_grid(&grid))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_board() {
+        let svg = Board::new("LO-\n_?K").unwrap().render_svg(0).unwrap();
+        assert!(svg.starts_with(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="104" height="70" viewBox="0 0 104 70">"#
+        ));
+        assert!(svg.ends_with("</svg>\n"));
+
+        // The gap has no cell, and the blank has no letter.
+        assert_eq!(svg.matches(r#"<rect class="normal_cell""#).count(), 5);
+        assert_eq!(svg.matches("<text").count(), 4);
+        assert!(svg.contains(r#"<rect class="normal_cell" x="3" y="3" width="30" height="30"/>"#));
+        assert!(svg.contains(r#"<text class="letter" x="86" y="52">K</text>"#));
+    }
+
+    #[test]
+    fn blackened_and_marked() {
+        let mut board = Board::new("LOK_").unwrap();
+        board.blacken(0, 0).unwrap();
+        board.mark_path(0, 1).unwrap();
+        board.mark_path(0, 1).unwrap();
+        board.blacken(0, 1).unwrap();
+
+        let svg = board.render_svg(board.move_count()).unwrap();
+        assert!(svg.contains(r#"<rect class="normal_cell blackened" x="3" y="3""#));
+        assert!(svg.contains(r#"<text class="letter blackened" x="18" y="18">L</text>"#));
+        assert!(svg.contains(r#"<rect class="normal_cell blackened pathmarked" x="37" y="3""#));
+        assert!(
+            svg.contains(r#"<text class="mark_count blackened pathmarked" x="65" y="15">3</text>"#)
+        );
+    }
+
+    #[test]
+    fn earlier_steps() {
+        let mut board = Board::new("LOK_").unwrap();
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 1).unwrap();
+
+        assert_eq!(
+            board.render_svg(0).unwrap(),
+            Board::new("LOK_").unwrap().render_svg(0).unwrap()
+        );
+        let svg = board.render_svg(1).unwrap();
+        assert_eq!(svg.matches(r#"class="normal_cell blackened""#).count(), 1);
+        assert!(svg.contains(r#"<text class="letter" x="52" y="18">O</text>"#));
+
+        assert_eq!(
+            board.render_svg(3).unwrap_err(),
+            "Step 3 is past the 2 moves that were made!"
+        );
+    }
+
+    #[test]
+    fn escapes_letters() {
+        let svg = Board::new("<&").unwrap().render_svg(0).unwrap();
+        assert!(svg.contains(">&lt;</text>"));
+        assert!(svg.contains(">&amp;</text>"));
+    }
+}
@@ -0,0 +1,288 @@
+use wasm_bindgen::prelude::*;
+
+use crate::grid::RC;
+use crate::keyword::{ExecutionProgress, KeywordRegistry};
+use crate::utils::Json;
+use crate::{Board, BoardState, Move, MoveError, SolutionResult, SR};
+
+/// What the simulation was doing at some point while checking a solution.
+#[derive(Clone, PartialEq, Debug)]
+pub enum TraceState {
+    /// Gathering a keyword, with the letters gathered so far and the moves used to gather them.
+    Gathering { keyword: String, moves: Vec<Move> },
+
+    /// Executing a keyword that was fully gathered, and how far along the execution is.
+    Executing {
+        keyword: String,
+        progress: ExecutionProgress,
+    },
+}
+
+impl TraceState {
+    fn new(state: &BoardState, keywords: &KeywordRegistry) -> TraceState {
+        match state {
+            BoardState::GatheringKeyword(keyword, moves) => TraceState::Gathering {
+                keyword: keyword.clone(),
+                moves: moves.clone(),
+            },
+            BoardState::ExecutingKeyword(id, progress) => TraceState::Executing {
+                keyword: keywords.get(*id).letters().to_string(),
+                progress: progress.clone(),
+            },
+        }
+    }
+
+    /// Gets the keyword gathered so far, or the keyword being executed.
+    pub fn keyword(&self) -> &str {
+        match self {
+            TraceState::Gathering { keyword, .. } => keyword,
+            TraceState::Executing { keyword, .. } => keyword,
+        }
+    }
+}
+
+/// What happened when checking a single move.
+#[derive(Clone, PartialEq, Debug)]
+pub struct TraceStep {
+    /// The move that was checked.
+    pub mv: Move,
+
+    /// The state of the simulation before the move.
+    pub before: TraceState,
+
+    /// The state of the simulation after the move, or `None` if the move was rejected.
+    pub after: Option<TraceState>,
+
+    /// Every cell the move blackened. Besides the targeted cell, this includes side effects like blackening a whole
+    /// keyword once its last letter is gathered.
+    pub blackened: Vec<RC>,
+
+    /// Why the move was rejected, if it was.
+    pub error: Option<MoveError>,
+}
+
+/// A step-by-step account of checking a solution, for debugging why it is or isn't correct.
+#[derive(PartialEq, Debug)]
+pub struct SolutionTrace {
+    /// One step for each move, stopping at the first move that was rejected.
+    pub steps: Vec<TraceStep>,
+
+    /// The same result as `check_solution`.
+    pub result: SolutionResult,
+}
+
+fn rc_json(rc: &RC) -> Json {
+    vec![rc.0, rc.1].into()
+}
+
+fn moves_json(moves: &[Move]) -> Json {
+    Json::Array(moves.iter().map(|mv| mv.to_string().into()).collect())
+}
+
+impl From<&ExecutionProgress> for Json {
+    fn from(progress: &ExecutionProgress) -> Json {
+        match progress {
+            ExecutionProgress::Started => Json::Object(vec![("kind", "Started".into())]),
+            ExecutionProgress::ChoseCell(rc) => {
+                Json::Object(vec![("kind", "ChoseCell".into()), ("cell", rc_json(rc))])
+            }
+            ExecutionProgress::ChoseLetter(letter) => Json::Object(vec![
+                ("kind", "ChoseLetter".into()),
+                ("letter", letter.to_string().into()),
+            ]),
+            ExecutionProgress::Finished => Json::Object(vec![("kind", "Finished".into())]),
+        }
+    }
+}
+
+impl From<&TraceState> for Json {
+    fn from(state: &TraceState) -> Json {
+        match state {
+            TraceState::Gathering { keyword, moves } => Json::Object(vec![
+                ("kind", "Gathering".into()),
+                ("keyword", keyword.as_str().into()),
+                ("moves", moves_json(moves)),
+            ]),
+            TraceState::Executing { keyword, progress } => Json::Object(vec![
+                ("kind", "Executing".into()),
+                ("keyword", keyword.as_str().into()),
+                ("progress", progress.into()),
+            ]),
+        }
+    }
+}
+
+impl From<&TraceStep> for Json {
+    fn from(step: &TraceStep) -> Json {
+        Json::Object(vec![
+            ("move", step.mv.to_string().into()),
+            ("before", (&step.before).into()),
+            ("after", step.after.as_ref().into()),
+            (
+                "blackened",
+                Json::Array(step.blackened.iter().map(rc_json).collect()),
+            ),
+            (
+                "error",
+                step.error.as_ref().map(|err| format!("{:?}", err)).into(),
+            ),
+        ])
+    }
+}
+
+impl From<&SolutionTrace> for Json {
+    fn from(trace: &SolutionTrace) -> Json {
+        let error_move_index = match &trace.result {
+            SR::ErrorOnMove(mv_num, _) => Some(*mv_num),
+            _ => None,
+        };
+
+        Json::Object(vec![
+            ("result", trace.result.name().into()),
+            ("error_move_index", error_move_index.into()),
+            (
+                "steps",
+                Json::Array(trace.steps.iter().map(Into::into).collect()),
+            ),
+        ])
+    }
+}
+
+#[wasm_bindgen]
+impl Board {
+    /// Checks the solution like `check`, but returns a step-by-step trace of the check as JSON. See `trace_solution`.
+    /// Moves are written in the same format as `export_moves`, cells as `[row, col]`, and errors by name, e.g.
+    /// `"AlreadyBlackened"`.
+    pub fn trace_solution_json(&self) -> String {
+        Json::from(&self.trace_solution()).to_string()
+    }
+}
+
+impl Board {
+    /// Checks the solution like `check_solution`, but also records what happened at each move: the state before and
+    /// after, which cells were blackened, and why the move was rejected, if it was.
+    ///
+    /// This replays all of the moves from the start, so it is slower than `check_solution`.
+    pub fn trace_solution(&self) -> SolutionTrace {
+        let mut sim = self.new_simulation();
+        let mut steps = vec![];
+
+        for (mv_num, mv) in self.moves().enumerate() {
+            let before = TraceState::new(&sim.state, &self.keywords);
+
+            match sim.apply(mv) {
+                Ok(()) => {
+                    let blackened = sim
+                        .last_changes
+                        .iter()
+                        .filter(|change| {
+                            !change.before.is_blackened() && change.after.is_blackened()
+                        })
+                        .map(|change| change.rc.clone())
+                        .collect();
+
+                    steps.push(TraceStep {
+                        mv: mv.clone(),
+                        before,
+                        after: Some(TraceState::new(&sim.state, &self.keywords)),
+                        blackened,
+                        error: None,
+                    });
+                }
+                Err(err) => {
+                    steps.push(TraceStep {
+                        mv: mv.clone(),
+                        before,
+                        after: None,
+                        blackened: vec![],
+                        error: Some(err.clone()),
+                    });
+
+                    return SolutionTrace {
+                        steps,
+                        result: SR::ErrorOnMove(mv_num, err),
+                    };
+                }
+            }
+        }
+
+        SolutionTrace {
+            steps,
+            result: sim.result(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ME;
+
+    #[test]
+    fn trace_correct_solution() {
+        let mut board = Board::new("LOK_").unwrap();
+        for col in 0..4 {
+            board.blacken(0, col).unwrap();
+        }
+
+        let trace = board.trace_solution();
+        assert_eq!(trace.result, SR::Correct);
+        assert_eq!(trace.result, board.check_solution());
+        assert_eq!(trace.steps.len(), 4);
+
+        // The keyword is only blackened once it's fully gathered.
+        assert_eq!(trace.steps[0].blackened, vec![]);
+        assert_eq!(trace.steps[1].after.as_ref().unwrap().keyword(), "LO");
+        assert_eq!(trace.steps[2].blackened, vec![RC(0, 0), RC(0, 1), RC(0, 2)]);
+        assert_eq!(
+            trace.steps[2].after,
+            Some(TraceState::Executing {
+                keyword: "LOK".to_string(),
+                progress: ExecutionProgress::Started,
+            })
+        );
+        assert_eq!(trace.steps[3].blackened, vec![RC(0, 3)]);
+        assert_eq!(
+            trace.steps[3].after,
+            Some(TraceState::Gathering {
+                keyword: String::new(),
+                moves: vec![],
+            })
+        );
+    }
+
+    #[test]
+    fn trace_stops_at_error() {
+        let mut board = Board::new("LOK_").unwrap();
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 2).unwrap();
+        board.blacken(0, 1).unwrap();
+
+        let trace = board.trace_solution();
+        assert_eq!(trace.result, board.check_solution());
+        assert_eq!(trace.steps.len(), 2);
+        assert_eq!(trace.steps[1].after, None);
+        assert_eq!(
+            trace.steps[1].error,
+            Some(ME::BlackenNotConnectedForKeyword)
+        );
+    }
+
+    #[test]
+    fn trace_json() {
+        let mut board = Board::new("LOK_").unwrap();
+        board.blacken(0, 0).unwrap();
+        board.blacken(0, 3).unwrap();
+
+        assert_eq!(
+            board.trace_solution_json(),
+            concat!(
+                r#"{"result":"ErrorOnMove","error_move_index":1,"steps":["#,
+                r#"{"move":"B 0 0","before":{"kind":"Gathering","keyword":"","moves":[]},"#,
+                r#""after":{"kind":"Gathering","keyword":"L","moves":["B 0 0"]},"blackened":[],"error":null},"#,
+                r#"{"move":"B 0 3","before":{"kind":"Gathering","keyword":"L","moves":["B 0 0"]},"#,
+                r#""after":null,"blackened":[],"error":"BlackenNotConnectedForKeyword"}]}"#
+            )
+        );
+    }
+}
@@ -1,10 +1,19 @@
-import { Board, BoardCell } from "lok-wasm";
+import { Board, BoardCell, encode_share_code, decode_share_code } from "lok-wasm";
 
 window.addEventListener("hashchange", onHashChange);
 window.addEventListener("keydown", onKeyDown);
 document.getElementById("check_solution").addEventListener("click", onClickCheckSolution);
+document.getElementById("explain_solution").addEventListener("click", onClickExplainSolution);
+document.getElementById("hint").addEventListener("click", onClickHint);
 document.getElementById("render_form").addEventListener("submit", onRenderSubmit);
 document.getElementById("undo").addEventListener("click", onClickUndo);
+document.getElementById("redo").addEventListener("click", onClickRedo);
+document.getElementById("next_branch").addEventListener("click", onClickNextBranch);
+document.getElementById("export_moves").addEventListener("click", onClickExportMoves);
+document.getElementById("import_moves").addEventListener("click", onClickImportMoves);
+document.getElementById("share_moves").addEventListener("click", onClickShareMoves);
+document.getElementById("download_image").addEventListener("click", onClickDownloadImage);
+document.getElementById("strict_mode").addEventListener("change", onStrictModeChange);
 
 {
     const modeElements = document.getElementsByName("mode");
@@ -16,9 +25,22 @@ document.getElementById("undo").addEventListener("click", onClickUndo);
 
 var g_lastModeEditState = false;
 
+// Puzzles in the URL hash start with this, followed by a code from `encode_share_code`. Older links have the puzzle
+// text itself, encoded with `encodeURIComponent`, which never produces an "=".
+const SHARE_CODE_PREFIX = "z=";
+
 var g_anchor = null;
 var g_board = null;
 
+// The cell to highlight after the author asks for an explanation of the solution.
+var g_explainedCell = null;
+
+// The cell to highlight after the player asks for a hint.
+var g_hintedCell = null;
+
+// How many states the search for a hint may visit before giving up, so that the page doesn't hang on a big puzzle.
+const HINT_MAX_STEPS = 200000;
+
 // If the hash/anchor of the URL has changed, load the newly specified puzzle
 function onHashChange() {
     const newAnchor = window.location.hash;
@@ -35,11 +57,32 @@ function onHashChange() {
             encodedPuzzle = encodeURIComponent("LO-_K-\nL_O_K_\nTLAK--");
         }
 
-        document.getElementById("puzzle_entry").value = decodeURIComponent(encodedPuzzle);
-        setPuzzle();
+        var puzzle = "";
+        var moves = "";
+        if (encodedPuzzle.startsWith(SHARE_CODE_PREFIX)) {
+            try {
+                const shared = decode_share_code(encodedPuzzle.substring(SHARE_CODE_PREFIX.length));
+                puzzle = shared.get_puzzle();
+                moves = shared.get_moves();
+            }
+            catch (ex) {
+                alert("Error reading puzzle link: " + ex);
+                return;
+            }
+        } else {
+            puzzle = decodeURIComponent(encodedPuzzle);
+        }
+
+        document.getElementById("puzzle_entry").value = puzzle;
+        setPuzzle(moves);
     }
 }
 
+// Gets a link to the given puzzle and moves, which may be empty.
+function getShareHash(puzzle, moves) {
+    return "#" + SHARE_CODE_PREFIX + encode_share_code(puzzle, moves);
+}
+
 function onKeyDown(evt) {
     switch (evt.key) {
         // ALT-z to undo.
@@ -50,6 +93,14 @@ function onKeyDown(evt) {
             break;
         }
 
+        // ALT-y to redo.
+        case "y": {
+            if (evt.altKey) {
+                onClickRedo();
+            }
+            break;
+        }
+
         // CTRL-Enter to set puzzle, when the puzzle text entry is in focus.
         case "Enter": {
             if (evt.ctrlKey) {
@@ -82,29 +133,54 @@ function onModeChange(evt) {
     const nowInModeEdit = document.getElementById("modeEdit").checked;
     if (nowInModeEdit != g_lastModeEditState) {
         g_lastModeEditState = nowInModeEdit;
+
+        // Explanations spoil the rules, so they're only offered to puzzle authors, who are editing the puzzle.
+        document.getElementById("explain_solution").style.display = nowInModeEdit ? "" : "none";
         renderBoard();
     }
 }
 
-function setPuzzle() {
+// Builds the board for the puzzle that was entered, then makes the moves, if any are given, e.g. from a shared link.
+function setPuzzle(moves = "") {
     const puzzle = document.getElementById("puzzle_entry").value;
     try {
-        g_board = Board.new(puzzle);
-        renderBoard();
-
-        const resultDisplay = document.getElementById("result_display");
-        resultDisplay.className = null;
-        resultDisplay.textContent = "Unsolved";
-
-        var newHash = "#" + encodeURIComponent(puzzle);
-        if (window.location.hash != newHash) {
-            console.log("setting hash to " + newHash);
-            window.location.hash = newHash;
-        }
+        g_board = Board.restore(puzzle);
+        g_board.set_strict(document.getElementById("strict_mode").checked);
     }
     catch (ex) {
         alert("Error rendering puzzle: " + ex);
+        return false;
+    }
+
+    if (moves != "") {
+        try {
+            g_board.import_moves(moves);
+        }
+        catch (ex) {
+            alert("Error importing moves: " + ex);
+            moves = "";
+        }
     }
+
+    renderBoard();
+
+    const resultDisplay = document.getElementById("result_display");
+    resultDisplay.className = null;
+    resultDisplay.textContent = "Unsolved";
+
+    // Remember the new hash before changing it, so that the change doesn't load the puzzle all over again.
+    var newHash = getShareHash(puzzle, moves);
+    if (window.location.hash != newHash) {
+        console.log("setting hash to " + newHash);
+        g_anchor = newHash;
+        window.location.hash = newHash;
+    }
+
+    return true;
+}
+
+function onStrictModeChange(evt) {
+    g_board.set_strict(document.getElementById("strict_mode").checked);
 }
 
 function onRenderSubmit(evt) {
@@ -124,18 +200,23 @@ function getMode() {
 
 function onCellClick(evt) {
     const cell = evt.currentTarget;
-    switch (getMode()) {
-        case "blacken": {
-            g_board.blacken(cell.boardRow, cell.boardCol);
-            renderBoard();
-            break;
-        }
-        case "markPath": {
-            g_board.mark_path(cell.boardRow, cell.boardCol);
-            renderBoard();
-            break;
+    try {
+        switch (getMode()) {
+            case "blacken": {
+                g_board.blacken(cell.boardRow, cell.boardCol);
+                renderBoard();
+                break;
+            }
+            case "markPath": {
+                g_board.mark_path(cell.boardRow, cell.boardCol);
+                renderBoard();
+                break;
+            }
         }
     }
+    catch (ex) {
+        alert("Error making move: " + ex);
+    }
 }
 
 function onLetterFocus(evt) {
@@ -149,7 +230,12 @@ function onLetterInput(evt) {
 
     const letterText = target.textContent;
     if (letterText.length > 0) {
-        g_board.change_letter(cell.boardRow, cell.boardCol, letterText.charAt(0));
+        try {
+            g_board.change_letter(cell.boardRow, cell.boardCol, letterText.charAt(0));
+        }
+        catch (ex) {
+            alert("Error changing letter: " + ex);
+        }
         renderBoard();
     }
 }
@@ -165,11 +251,105 @@ function onClickCheckSolution(evt) {
     }
 }
 
+// Spoils why the solution is wrong, for puzzle authors who explicitly ask.
+function onClickExplainSolution(evt) {
+    const details = g_board.check_detailed();
+    var explanation = details.get_result();
+    if (details.get_move_index() !== undefined) {
+        explanation += " on move " + (details.get_move_index() + 1) + ": " + details.get_error();
+    }
+
+    g_explainedCell = null;
+    if (details.get_row() !== undefined) {
+        g_explainedCell = { row: details.get_row(), col: details.get_col() };
+        explanation += " at row " + details.get_row() + ", col " + details.get_col();
+    }
+
+    const resultDisplay = document.getElementById("result_display");
+    resultDisplay.className = (details.get_result() == "Correct") ? "result_success" : "result_fail";
+    resultDisplay.textContent = explanation;
+    renderBoard();
+    g_explainedCell = null;
+}
+
+// Nudges the player by highlighting a cell they could target next, without saying what to do with it.
+function onClickHint(evt) {
+    const resultDisplay = document.getElementById("result_display");
+    var hint;
+    try {
+        hint = g_board.get_hint(HINT_MAX_STEPS);
+    }
+    catch (ex) {
+        resultDisplay.className = "result_fail";
+        resultDisplay.textContent = "Couldn't find a hint in time.";
+        return;
+    }
+
+    if (hint === undefined) {
+        resultDisplay.className = "result_fail";
+        resultDisplay.textContent = "No hint. Try undoing.";
+        return;
+    }
+
+    g_hintedCell = { row: hint.get_row(), col: hint.get_col() };
+    renderBoard();
+    g_hintedCell = null;
+}
+
 function onClickUndo(evt) {
     g_board.undo();
     renderBoard();
 }
 
+function onClickRedo(evt) {
+    g_board.redo();
+    renderBoard();
+}
+
+// Switches to the next branch at the latest point where the player went back and tried something different.
+function onClickNextBranch(evt) {
+    for (var step = g_board.move_count(); step >= 0; step--) {
+        const branchCount = g_board.branch_count(step);
+        if (branchCount > 1) {
+            const activeBranch = g_board.active_branch(step);
+            g_board.switch_branch(step, (activeBranch + 1) % branchCount);
+            renderBoard();
+            break;
+        }
+    }
+}
+
+function onClickExportMoves(evt) {
+    document.getElementById("moves_entry").value = g_board.export_moves();
+}
+
+function onClickImportMoves(evt) {
+    try {
+        g_board.import_moves(document.getElementById("moves_entry").value);
+        renderBoard();
+    }
+    catch (ex) {
+        alert("Error importing moves: " + ex);
+    }
+}
+
+function onClickShareMoves(evt) {
+    const puzzle = document.getElementById("puzzle_entry").value;
+    const url = window.location.origin + window.location.pathname + getShareHash(puzzle, g_board.export_moves());
+    prompt("Link to this puzzle with your moves so far:", url);
+}
+
+function onClickDownloadImage(evt) {
+    const svg = g_board.render_svg(g_board.move_count());
+    const url = URL.createObjectURL(new Blob([svg], { type: "image/svg+xml" }));
+
+    const link = document.createElement("a");
+    link.href = url;
+    link.download = "lok.svg";
+    link.click();
+    URL.revokeObjectURL(url);
+}
+
 function renderBoard() {
     const width = g_board.width();
     const height = g_board.height();
@@ -225,6 +405,14 @@ function renderBoard() {
             if (boardCell.is_marked_for_path()) {
                 cell.classList.add("pathmarked");
             }
+
+            if (g_explainedCell && g_explainedCell.row == r && g_explainedCell.col == c) {
+                cell.classList.add("explained");
+            }
+
+            if (g_hintedCell && g_hintedCell.row == r && g_hintedCell.col == c) {
+                cell.classList.add("hinted");
+            }
             row.appendChild(cell);
         }
         boardTable.appendChild(row);